@@ -1,8 +1,27 @@
 // Automatically generated AST
+use std::rc::Rc;
+
+use crate::ast::stmt::Stmt;
+use crate::ast::subscript::Subscript;
+use crate::token::Token;
+use crate::units::Unit;
+use crate::value::Value;
+
 pub enum Expr {
     BinaryExpr(Box<BinaryExpr>),
     UnaryExpr(Box<UnaryExpr>),
     LiteralValue(Box<LiteralValue>),
+    ComplexLiteral(Box<ComplexLiteral>),
+    Interpolation(Box<Interpolation>),
+    TensorLiteral(Box<TensorLiteral>),
+    Grouping(Box<Grouping>),
+    Variable(Box<Variable>),
+    Assign(Box<Assign>),
+    Call(Box<Call>),
+    Index(Box<Index>),
+    Lambda(Box<Lambda>),
+    Range(Box<Range>),
+    Comprehension(Box<Comprehension>),
 }
 pub struct BinaryExpr {
     pub left: Expr,
@@ -14,7 +33,58 @@ pub struct UnaryExpr {
     pub right: Expr,
 }
 pub struct LiteralValue {
-    pub number: f64,
-    pub string: String,
+    pub value: Value,
+    pub unit: Option<Unit>,
+}
+pub struct ComplexLiteral {
+    pub real: f64,
+    pub imaginary: f64,
+}
+pub struct Interpolation {
+    pub strings: Vec<String>,
+    pub expressions: Vec<Expr>,
+}
+pub struct TensorLiteral {
+    pub bracket: Token,
+    pub shape: Vec<usize>,
+    pub elements: Vec<Expr>,
+    pub rows: bool,
+}
+pub struct Grouping {
+    pub expression: Expr,
+}
+pub struct Variable {
+    pub name: Token,
+}
+pub struct Assign {
+    pub name: Token,
+    pub value: Expr,
+}
+pub struct Call {
+    pub callee: Expr,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+    pub keywords: Vec<(Token, Expr)>,
+}
+pub struct Index {
+    pub object: Expr,
+    pub bracket: Token,
+    pub subscripts: Vec<Subscript>,
+}
+pub struct Lambda {
+    pub keyword: Token,
+    pub params: Vec<Token>,
+    pub defaults: Vec<Option<Expr>>,
+    pub body: Rc<Vec<Stmt>>,
+}
+pub struct Range {
+    pub start: Expr,
+    pub operator: Token,
+    pub end: Expr,
+    pub step: Option<Expr>,
+}
+pub struct Comprehension {
+    pub bracket: Token,
+    pub body: Expr,
+    pub generators: Vec<(Token, Expr)>,
 }
-
@@ -0,0 +1,4 @@
+pub mod expr;
+pub mod stmt;
+pub mod subscript;
+pub mod visitor;
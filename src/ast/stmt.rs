@@ -0,0 +1,48 @@
+// Automatically generated AST
+use std::rc::Rc;
+
+use crate::ast::expr::Expr;
+use crate::token::Token;
+
+pub enum Stmt {
+    ExpressionStmt(Box<ExpressionStmt>),
+    PrintStmt(Box<PrintStmt>),
+    VarStmt(Box<VarStmt>),
+    BlockStmt(Box<BlockStmt>),
+    IfStmt(Box<IfStmt>),
+    WhileStmt(Box<WhileStmt>),
+    FunctionStmt(Box<FunctionStmt>),
+    ReturnStmt(Box<ReturnStmt>),
+}
+pub struct ExpressionStmt {
+    pub expression: Expr,
+}
+pub struct PrintStmt {
+    pub expression: Expr,
+}
+pub struct VarStmt {
+    pub name: Token,
+    pub initializer: Option<Expr>,
+}
+pub struct BlockStmt {
+    pub statements: Vec<Stmt>,
+}
+pub struct IfStmt {
+    pub condition: Expr,
+    pub then_branch: Stmt,
+    pub else_branch: Option<Stmt>,
+}
+pub struct WhileStmt {
+    pub condition: Expr,
+    pub body: Stmt,
+}
+pub struct FunctionStmt {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub defaults: Vec<Option<Expr>>,
+    pub body: Rc<Vec<Stmt>>,
+}
+pub struct ReturnStmt {
+    pub keyword: Token,
+    pub value: Option<Expr>,
+}
@@ -0,0 +1,20 @@
+// Automatically generated AST
+use crate::ast::expr::Expr;
+use crate::token::Token;
+
+pub enum Subscript {
+    IndexSubscript(Box<IndexSubscript>),
+    SliceSubscript(Box<SliceSubscript>),
+    EllipsisSubscript(Box<EllipsisSubscript>),
+}
+pub struct IndexSubscript {
+    pub index: Expr,
+}
+pub struct SliceSubscript {
+    pub start: Option<Expr>,
+    pub stop: Option<Expr>,
+    pub step: Option<Expr>,
+}
+pub struct EllipsisSubscript {
+    pub token: Token,
+}
@@ -1,23 +1,74 @@
-mod expr;
-
-use crate::expr::{Expr, BinaryExpr, UnaryExpr, LiteralValue};
+use crate::ast::expr::{Expr, BinaryExpr, UnaryExpr, LiteralValue, ComplexLiteral, Interpolation, TensorLiteral, Grouping, Variable, Assign, Call, Index, Lambda, Range, Comprehension};
+use crate::ast::stmt::{Stmt, ExpressionStmt, PrintStmt, VarStmt, BlockStmt, IfStmt, WhileStmt, FunctionStmt, ReturnStmt};
 
 pub trait ExprVisitor<T> {
     fn visit_binary_expr(&self, expr: &BinaryExpr) -> T;
     fn visit_unary_expr(&self, expr: &UnaryExpr) -> T;
     fn visit_literal_value(&self, expr: &LiteralValue) -> T;
+    fn visit_complex_literal(&self, expr: &ComplexLiteral) -> T;
+    fn visit_interpolation(&self, expr: &Interpolation) -> T;
+    fn visit_tensor_literal(&self, expr: &TensorLiteral) -> T;
+    fn visit_grouping(&self, expr: &Grouping) -> T;
+    fn visit_variable(&self, expr: &Variable) -> T;
+    fn visit_assign(&self, expr: &Assign) -> T;
+    fn visit_call(&self, expr: &Call) -> T;
+    fn visit_index(&self, expr: &Index) -> T;
+    fn visit_lambda(&self, expr: &Lambda) -> T;
+    fn visit_range(&self, expr: &Range) -> T;
+    fn visit_comprehension(&self, expr: &Comprehension) -> T;
 }
 
 pub trait ExprAccept<T> {
     fn accept<V: ExprVisitor<T>>(&self, visitor: &V) -> T;
 }
 
-impl ExprAccept<String> for Expr {
-    fn accept<V: ExprVisitor<String>>(&self, visitor: &V) -> String {
+impl<T> ExprAccept<T> for Expr {
+    fn accept<V: ExprVisitor<T>>(&self, visitor: &V) -> T {
         match self {
             Expr::BinaryExpr(expr) => visitor.visit_binary_expr(expr),
             Expr::UnaryExpr(expr) => visitor.visit_unary_expr(expr),
             Expr::LiteralValue(expr) => visitor.visit_literal_value(expr),
+            Expr::ComplexLiteral(expr) => visitor.visit_complex_literal(expr),
+            Expr::Interpolation(expr) => visitor.visit_interpolation(expr),
+            Expr::TensorLiteral(expr) => visitor.visit_tensor_literal(expr),
+            Expr::Grouping(expr) => visitor.visit_grouping(expr),
+            Expr::Variable(expr) => visitor.visit_variable(expr),
+            Expr::Assign(expr) => visitor.visit_assign(expr),
+            Expr::Call(expr) => visitor.visit_call(expr),
+            Expr::Index(expr) => visitor.visit_index(expr),
+            Expr::Lambda(expr) => visitor.visit_lambda(expr),
+            Expr::Range(expr) => visitor.visit_range(expr),
+            Expr::Comprehension(expr) => visitor.visit_comprehension(expr),
         }
     }
-}
\ No newline at end of file
+}
+
+pub trait StmtVisitor<T> {
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> T;
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> T;
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> T;
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> T;
+    fn visit_if_stmt(&self, stmt: &IfStmt) -> T;
+    fn visit_while_stmt(&self, stmt: &WhileStmt) -> T;
+    fn visit_function_stmt(&self, stmt: &FunctionStmt) -> T;
+    fn visit_return_stmt(&self, stmt: &ReturnStmt) -> T;
+}
+
+pub trait StmtAccept<T> {
+    fn accept<V: StmtVisitor<T>>(&self, visitor: &V) -> T;
+}
+
+impl<T> StmtAccept<T> for Stmt {
+    fn accept<V: StmtVisitor<T>>(&self, visitor: &V) -> T {
+        match self {
+            Stmt::ExpressionStmt(stmt) => visitor.visit_expression_stmt(stmt),
+            Stmt::PrintStmt(stmt) => visitor.visit_print_stmt(stmt),
+            Stmt::VarStmt(stmt) => visitor.visit_var_stmt(stmt),
+            Stmt::BlockStmt(stmt) => visitor.visit_block_stmt(stmt),
+            Stmt::IfStmt(stmt) => visitor.visit_if_stmt(stmt),
+            Stmt::WhileStmt(stmt) => visitor.visit_while_stmt(stmt),
+            Stmt::FunctionStmt(stmt) => visitor.visit_function_stmt(stmt),
+            Stmt::ReturnStmt(stmt) => visitor.visit_return_stmt(stmt),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+
+use crate::complex::Complex;
+use crate::value::Value;
+
+/// A native function. The scanner and parser treat `sin(x)` like any other
+/// call; the name is resolved against `BUILTINS` when the program runs, so a
+/// new math function only needs an entry in that table.
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: fn(&[Value]) -> Result<Value, String>,
+}
+
+impl fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<builtin {}>", self.name)
+    }
+}
+
+pub static BUILTINS: &[Builtin] = &[
+    Builtin { name: "sin", arity: 1, function: |args| unary(args, f64::sin, Complex::sin) },
+    Builtin { name: "cos", arity: 1, function: |args| unary(args, f64::cos, Complex::cos) },
+    Builtin { name: "tan", arity: 1, function: |args| unary(args, f64::tan, Complex::tan) },
+    Builtin { name: "asin", arity: 1, function: |args| unary(args, f64::asin, Complex::asin) },
+    Builtin { name: "acos", arity: 1, function: |args| unary(args, f64::acos, Complex::acos) },
+    Builtin { name: "atan", arity: 1, function: |args| unary(args, f64::atan, Complex::atan) },
+    Builtin { name: "sinh", arity: 1, function: |args| unary(args, f64::sinh, Complex::sinh) },
+    Builtin { name: "cosh", arity: 1, function: |args| unary(args, f64::cosh, Complex::cosh) },
+    Builtin { name: "tanh", arity: 1, function: |args| unary(args, f64::tanh, Complex::tanh) },
+    Builtin { name: "log", arity: 1, function: |args| unary(args, f64::ln, Complex::ln) },
+    Builtin { name: "log10", arity: 1, function: |args| unary(args, f64::log10, Complex::log10) },
+    Builtin { name: "log2", arity: 1, function: |args| unary(args, f64::log2, Complex::log2) },
+    Builtin { name: "exp", arity: 1, function: |args| unary(args, f64::exp, Complex::exp) },
+    Builtin { name: "sqrt", arity: 1, function: |args| unary(args, f64::sqrt, Complex::sqrt) },
+    Builtin { name: "abs", arity: 1, function: abs },
+    Builtin { name: "floor", arity: 1, function: |args| real_unary("floor", args, f64::floor) },
+    Builtin { name: "ceil", arity: 1, function: |args| real_unary("ceil", args, f64::ceil) },
+    Builtin { name: "atan2", arity: 2, function: |args| real_binary("atan2", args, f64::atan2) },
+    Builtin { name: "pow", arity: 2, function: pow },
+];
+
+// Applies a function with both a real and a complex form; real arguments stay
+// real and complex arguments stay complex.
+fn unary(args: &[Value], real: fn(f64) -> f64, complex: fn(Complex) -> Complex) -> Result<Value, String> {
+    match &args[0] {
+        Value::Number(x) => Ok(Value::Number(real(*x))),
+        Value::Complex(z) => Ok(Value::Complex(complex(*z))),
+        other => Err(format!("Expected a number but got {}.", other.type_name())),
+    }
+}
+
+fn real_unary(name: &str, args: &[Value], real: fn(f64) -> f64) -> Result<Value, String> {
+    match &args[0] {
+        Value::Number(x) => Ok(Value::Number(real(*x))),
+        other => Err(format!("'{}' expects a real number but got {}.", name, other.type_name())),
+    }
+}
+
+fn real_binary(name: &str, args: &[Value], real: fn(f64, f64) -> f64) -> Result<Value, String> {
+    match (&args[0], &args[1]) {
+        (Value::Number(x), Value::Number(y)) => Ok(Value::Number(real(*x, *y))),
+        (x, y) => Err(format!(
+            "'{}' expects real numbers but got {} and {}.",
+            name,
+            x.type_name(),
+            y.type_name()
+        )),
+    }
+}
+
+fn abs(args: &[Value]) -> Result<Value, String> {
+    match &args[0] {
+        Value::Number(x) => Ok(Value::Number(x.abs())),
+        Value::Complex(z) => Ok(Value::Number(z.abs())),
+        other => Err(format!("Expected a number but got {}.", other.type_name())),
+    }
+}
+
+fn pow(args: &[Value]) -> Result<Value, String> {
+    match (&args[0], &args[1]) {
+        (Value::Number(x), Value::Number(y)) => Ok(Value::Number(x.powf(*y))),
+        (Value::Number(x), Value::Complex(y)) => Ok(Value::Complex(Complex::from_real(*x).powc(*y))),
+        (Value::Complex(x), Value::Number(y)) => Ok(Value::Complex(x.powc(Complex::from_real(*y)))),
+        (Value::Complex(x), Value::Complex(y)) => Ok(Value::Complex(x.powc(*y))),
+        (x, y) => Err(format!(
+            "'pow' expects numbers but got {} and {}.",
+            x.type_name(),
+            y.type_name()
+        )),
+    }
+}
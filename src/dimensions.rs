@@ -0,0 +1,430 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::errors::MyError;
+use crate::ast::expr::{Assign, BinaryExpr, Call, ComplexLiteral, Expr, Grouping, Comprehension, Index, Interpolation, Lambda, LiteralValue, Range, TensorLiteral, UnaryExpr, Variable};
+use crate::ast::stmt::{BlockStmt, ExpressionStmt, FunctionStmt, IfStmt, PrintStmt, ReturnStmt, Stmt, VarStmt, WhileStmt};
+use crate::token::{Span, Token, TokenType};
+use crate::units::Unit;
+use crate::value::Value;
+use crate::ast::subscript::Subscript;
+use crate::ast::visitor::{ExprAccept, ExprVisitor, StmtAccept, StmtVisitor};
+
+/// Compile-time dimensional analysis. Units are inferred bottom-up from the
+/// annotations on numeric literals and carried through variables; `None`
+/// means the unit is unknown, as for a function parameter, and never causes
+/// an error.
+pub struct DimensionChecker {
+    errors: RefCell<Vec<MyError>>,
+    // The unit of each variable in scope, innermost scope last.
+    scopes: RefCell<Vec<HashMap<String, Option<Unit>>>>,
+}
+
+/// Checks every expression in `statements` and returns each dimension
+/// mismatch found.
+pub fn check_dimensions(statements: &[Stmt]) -> Vec<MyError> {
+    let checker = DimensionChecker {
+        errors: RefCell::new(Vec::new()),
+        scopes: RefCell::new(vec![HashMap::new()]),
+    };
+    for statement in statements {
+        statement.accept(&checker);
+    }
+    checker.errors.into_inner()
+}
+
+impl DimensionChecker {
+    fn error(&self, message: String, span: Span) {
+        self.errors.borrow_mut().push(MyError::UnitError { message, span });
+    }
+
+    // Both sides of `+`, `-`, `%` and comparisons must share a unit.
+    fn require_same(&self, action: &str, left: Option<Unit>, right: Option<Unit>, span: Span) -> Option<Unit> {
+        match (left, right) {
+            (Some(l), Some(r)) if l != r => {
+                self.error(format!("Cannot {} {} and {}.", action, l, r), span);
+                None
+            }
+            _ => left.or(right),
+        }
+    }
+
+    fn define(&self, name: &Token, unit: Option<Unit>) {
+        if let Some(scope) = self.scopes.borrow_mut().last_mut() {
+            scope.insert(name.name().to_string(), unit);
+        }
+    }
+
+    fn lookup(&self, name: &Token) -> Option<Unit> {
+        let scopes = self.scopes.borrow();
+        scopes.iter().rev().find_map(|scope| scope.get(name.name())).copied().flatten()
+    }
+
+    // Whether the program defines `name`, which then hides any builtin of
+    // that name.
+    fn is_defined(&self, name: &Token) -> bool {
+        self.scopes.borrow().iter().any(|scope| scope.contains_key(name.name()))
+    }
+
+    // Runs `check` in a new scope, so the variables it defines are forgotten
+    // afterwards.
+    fn in_scope(&self, check: impl FnOnce()) {
+        self.scopes.borrow_mut().push(HashMap::new());
+        check();
+        self.scopes.borrow_mut().pop();
+    }
+
+    // A function's parameters can be passed anything, so their units are
+    // unknown inside its body.
+    fn check_function(&self, params: &[Token], defaults: &[Option<Expr>], body: &[Stmt]) {
+        for default in defaults.iter().flatten() {
+            default.accept(self);
+        }
+        self.in_scope(|| {
+            for param in params {
+                self.define(param, None);
+            }
+            for statement in body {
+                statement.accept(self);
+            }
+        });
+    }
+
+    // A unit computed by `mul`, `div` or `powi`, which is `None` when an
+    // exponent overflowed.
+    fn require_in_range(&self, unit: Option<Unit>, span: Span) -> Option<Unit> {
+        if unit.is_none() {
+            self.error("Unit exponent out of range.".to_string(), span);
+        }
+        unit
+    }
+
+    fn require_dimensionless(&self, name: &str, argument: Option<Unit>, span: Span) {
+        if let Some(unit) = argument {
+            if !unit.is_dimensionless() {
+                self.error(format!("'{}' expects a dimensionless argument but got {}.", name, unit), span);
+            }
+        }
+    }
+
+    // Units of the math builtins; `None` for anything that is not one.
+    fn builtin_unit(&self, name: &str, arguments: &[Option<Unit>], span: Span) -> Option<Unit> {
+        let first = arguments.first().copied().flatten();
+        match name {
+            "sqrt" => {
+                let unit = first?;
+                let root = unit.sqrt();
+                if root.is_none() {
+                    self.error(format!("Cannot take the square root of {}.", unit), span);
+                }
+                root
+            }
+            "sin" | "cos" | "tan" | "sinh" | "cosh" | "tanh" => {
+                if first != Some(Unit::ANGLE) {
+                    self.require_dimensionless(name, first, span);
+                }
+                Some(Unit::DIMENSIONLESS)
+            }
+            "asin" | "acos" | "atan" => {
+                self.require_dimensionless(name, first, span);
+                Some(Unit::ANGLE)
+            }
+            "atan2" => {
+                let second = arguments.get(1).copied().flatten();
+                self.require_same("take atan2 of", first, second, span);
+                Some(Unit::ANGLE)
+            }
+            "exp" | "log" | "log10" | "log2" => {
+                self.require_dimensionless(name, first, span);
+                Some(Unit::DIMENSIONLESS)
+            }
+            "abs" | "floor" | "ceil" => first,
+            _ => None,
+        }
+    }
+}
+
+// The exponent of `x ^ 2` or `x ^ -1`, when it is a whole-number literal.
+fn constant_exponent(expr: &Expr) -> Option<i8> {
+    match expr {
+        Expr::LiteralValue(literal) => match literal.value {
+            Value::Number(n) if n.fract() == 0.0 && n.abs() <= i8::MAX as f64 => Some(n as i8),
+            _ => None,
+        },
+        Expr::UnaryExpr(unary) if unary.operator.token_type == TokenType::Minus => {
+            constant_exponent(&unary.right).map(|n| -n)
+        }
+        Expr::Grouping(grouping) => constant_exponent(&grouping.expression),
+        _ => None,
+    }
+}
+
+impl ExprVisitor<Option<Unit>> for DimensionChecker {
+    fn visit_binary_expr(&self, expr: &BinaryExpr) -> Option<Unit> {
+        let left = expr.left.accept(self);
+        let right = expr.right.accept(self);
+        let span = expr.operator.span();
+        match expr.operator.token_type {
+            TokenType::Plus => self.require_same("add", left, right, span),
+            TokenType::Minus => self.require_same("subtract", left, right, span),
+            TokenType::Percent => self.require_same("take the remainder of", left, right, span),
+            TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::EqualEqual
+            | TokenType::BangEqual => {
+                self.require_same("compare", left, right, span);
+                Some(Unit::DIMENSIONLESS)
+            }
+            // Tensor products multiply the units of their elements.
+            TokenType::Star
+            | TokenType::TensorMul
+            | TokenType::TensorProduct
+            | TokenType::TensorDot
+            | TokenType::TensorCross => self.require_in_range(left?.checked_mul(right?), span),
+            TokenType::Slash => self.require_in_range(left?.checked_div(right?), span),
+            TokenType::Caret => {
+                let unit = left?;
+                if unit.is_dimensionless() {
+                    return Some(unit);
+                }
+                match constant_exponent(&expr.right) {
+                    Some(exponent) => self.require_in_range(unit.checked_powi(exponent), span),
+                    None => {
+                        self.error(format!("Cannot raise {} to a power that is not an integer constant.", unit), span);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn visit_unary_expr(&self, expr: &UnaryExpr) -> Option<Unit> {
+        let right = expr.right.accept(self);
+        match expr.operator.token_type {
+            TokenType::Minus | TokenType::Plus => right,
+            _ => None,
+        }
+    }
+
+    fn visit_literal_value(&self, expr: &LiteralValue) -> Option<Unit> {
+        match expr.value {
+            Value::Number(_) => Some(expr.unit.unwrap_or(Unit::DIMENSIONLESS)),
+            _ => None,
+        }
+    }
+
+    fn visit_complex_literal(&self, _expr: &ComplexLiteral) -> Option<Unit> {
+        Some(Unit::DIMENSIONLESS)
+    }
+
+    fn visit_interpolation(&self, expr: &Interpolation) -> Option<Unit> {
+        for expression in &expr.expressions {
+            expression.accept(self);
+        }
+        None
+    }
+
+    // A tensor's elements share one unit.
+    fn visit_tensor_literal(&self, expr: &TensorLiteral) -> Option<Unit> {
+        let span = expr.bracket.span();
+        expr.elements
+            .iter()
+            .map(|element| element.accept(self))
+            .reduce(|unit, element| self.require_same("mix", unit, element, span))
+            .flatten()
+    }
+
+    fn visit_grouping(&self, expr: &Grouping) -> Option<Unit> {
+        expr.expression.accept(self)
+    }
+
+    fn visit_variable(&self, expr: &Variable) -> Option<Unit> {
+        self.lookup(&expr.name)
+    }
+
+    // A variable keeps one unit: assigning a value with a different unit is
+    // an error, and assigning to one whose unit is unknown gives it a unit.
+    fn visit_assign(&self, expr: &Assign) -> Option<Unit> {
+        let value = expr.value.accept(self);
+        let held = {
+            let mut scopes = self.scopes.borrow_mut();
+            let Some(slot) = scopes.iter_mut().rev().find_map(|scope| scope.get_mut(expr.name.name())) else {
+                return value;
+            };
+            if slot.is_none() {
+                *slot = value;
+            }
+            *slot
+        };
+        if let (Some(held), Some(assigned)) = (held, value) {
+            if held != assigned {
+                self.error(
+                    format!("Cannot assign {} to '{}', which holds {}.", assigned, expr.name.name(), held),
+                    expr.name.span(),
+                );
+            }
+        }
+        value
+    }
+
+    fn visit_call(&self, expr: &Call) -> Option<Unit> {
+        let arguments: Vec<Option<Unit>> = expr.arguments.iter().map(|argument| argument.accept(self)).collect();
+        for (_, argument) in &expr.keywords {
+            argument.accept(self);
+        }
+        match &expr.callee {
+            Expr::Variable(callee) if !self.is_defined(&callee.name) => {
+                self.builtin_unit(callee.name.name(), &arguments, callee.name.span())
+            }
+            callee => {
+                callee.accept(self);
+                None
+            }
+        }
+    }
+
+    // Elements keep the tensor's unit; the subscripts are only checked.
+    fn visit_index(&self, expr: &Index) -> Option<Unit> {
+        for subscript in &expr.subscripts {
+            match subscript {
+                Subscript::IndexSubscript(subscript) => {
+                    subscript.index.accept(self);
+                }
+                Subscript::SliceSubscript(subscript) => {
+                    for bound in [&subscript.start, &subscript.stop, &subscript.step].into_iter().flatten() {
+                        bound.accept(self);
+                    }
+                }
+                Subscript::EllipsisSubscript(_) => {}
+            }
+        }
+        expr.object.accept(self)
+    }
+
+    fn visit_lambda(&self, expr: &Lambda) -> Option<Unit> {
+        self.check_function(&expr.params, &expr.defaults, &expr.body);
+        None
+    }
+
+    fn visit_range(&self, expr: &Range) -> Option<Unit> {
+        let span = expr.operator.span();
+        let start = expr.start.accept(self);
+        let end = expr.end.accept(self);
+        let unit = self.require_same("make a range from", start, end, span);
+        match &expr.step {
+            Some(step) => {
+                let step = step.accept(self);
+                self.require_same("step a range of", unit, step, span)
+            }
+            None => unit,
+        }
+    }
+
+    // Each variable takes the unit of the tensor it iterates over, and the
+    // result has the body's unit.
+    fn visit_comprehension(&self, expr: &Comprehension) -> Option<Unit> {
+        let mut unit = None;
+        self.in_scope(|| {
+            for (name, iterable) in &expr.generators {
+                let iterable = iterable.accept(self);
+                self.define(name, iterable);
+            }
+            unit = expr.body.accept(self);
+        });
+        unit
+    }
+}
+
+impl StmtVisitor<()> for DimensionChecker {
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) {
+        stmt.expression.accept(self);
+    }
+
+    fn visit_print_stmt(&self, stmt: &PrintStmt) {
+        stmt.expression.accept(self);
+    }
+
+    fn visit_var_stmt(&self, stmt: &VarStmt) {
+        let unit = stmt.initializer.as_ref().and_then(|initializer| initializer.accept(self));
+        self.define(&stmt.name, unit);
+    }
+
+    fn visit_block_stmt(&self, stmt: &BlockStmt) {
+        self.in_scope(|| {
+            for statement in &stmt.statements {
+                statement.accept(self);
+            }
+        });
+    }
+
+    fn visit_if_stmt(&self, stmt: &IfStmt) {
+        stmt.condition.accept(self);
+        stmt.then_branch.accept(self);
+        if let Some(else_branch) = &stmt.else_branch {
+            else_branch.accept(self);
+        }
+    }
+
+    fn visit_while_stmt(&self, stmt: &WhileStmt) {
+        stmt.condition.accept(self);
+        stmt.body.accept(self);
+    }
+
+    fn visit_function_stmt(&self, stmt: &FunctionStmt) {
+        self.define(&stmt.name, None);
+        self.check_function(&stmt.params, &stmt.defaults, &stmt.body);
+    }
+
+    fn visit_return_stmt(&self, stmt: &ReturnStmt) {
+        if let Some(value) = &stmt.value {
+            value.accept(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    fn unit_errors(source: &str) -> Vec<String> {
+        let (tokens, errors) = Scanner::new(source).scan_tokens();
+        assert!(errors.is_empty(), "scan errors: {:?}", errors);
+        let (statements, errors) = Parser::new(tokens).parse();
+        assert!(errors.is_empty(), "parse errors: {:?}", errors);
+        check_dimensions(&statements).iter().map(|error| error.to_string()).collect()
+    }
+
+    #[test]
+    fn variables_keep_the_unit_of_their_initializer() {
+        let errors = unit_errors("var g = 9.81 m/s^2; var t = 2 s; print g + t;");
+        assert_eq!(errors, ["[line 1, column 42] Unit error: Cannot add m/s^2 and s."]);
+        assert!(unit_errors("var g = 9.81 m/s^2; var t = 2 s; print g * t * t + 1 m;").is_empty());
+    }
+
+    #[test]
+    fn block_scopes_shadow_and_restore_units() {
+        assert!(unit_errors("var x = 1 m; { var x = 2 s; print x + 1 s; } print x + 1 m;").is_empty());
+        assert!(unit_errors("var x = 1 m; fun f(x) { return x + 1 s; }").is_empty());
+    }
+
+    #[test]
+    fn assignments_must_keep_a_variables_unit() {
+        let errors = unit_errors("var x = 1 m; x = 2 s;");
+        assert_eq!(errors, ["[line 1, column 14] Unit error: Cannot assign s to 'x', which holds m."]);
+        let errors = unit_errors("var x; x = 1 m; print x + 1 s;");
+        assert_eq!(errors, ["[line 1, column 25] Unit error: Cannot add m and s."]);
+    }
+
+    #[test]
+    fn definitions_hide_builtins_of_the_same_name() {
+        assert!(unit_errors("fun sqrt(x) { return x; } print sqrt(2 m);").is_empty());
+        assert!(unit_errors("{ var exp = fun (y) => y; print exp(3 m); }").is_empty());
+        let errors = unit_errors("{ var exp = fun (y) => y; } print exp(3 m);");
+        assert_eq!(errors, ["[line 1, column 35] Unit error: 'exp' expects a dimensionless argument but got m."]);
+    }
+}
@@ -0,0 +1,63 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::errors::MyError;
+use crate::token::Token;
+use crate::value::Value;
+
+/// The variables of one scope, chained to the scope that encloses it.
+/// Scopes are shared so that closures keep the scope they were created in.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    /// Declares `name` in this scope, replacing any earlier declaration.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value, MyError> {
+        if let Some(value) = self.values.get(name.name()) {
+            return Ok(value.clone());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name),
+            None => Err(undefined(name)),
+        }
+    }
+
+    /// Assigns to the innermost declaration of `name`; assigning to a
+    /// variable that was never declared is an error.
+    pub fn assign(&mut self, name: &Token, value: Value) -> Result<(), MyError> {
+        if let Some(slot) = self.values.get_mut(name.name()) {
+            *slot = value;
+            return Ok(());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(undefined(name)),
+        }
+    }
+}
+
+fn undefined(name: &Token) -> MyError {
+    MyError::RuntimeError {
+        message: format!("Undefined variable '{}'.", name.name()),
+        span: name.span(),
+    }
+}
@@ -1,10 +1,15 @@
 use std::fmt;
 
+use crate::token::{Span, Token, TokenType};
+
 #[derive(Debug)]
 pub enum MyError {
     CommandLineArgs,
     FileReadError { path: String, error: std::io::Error },
-    ParsingError(String),
+    ScanError { message: String, span: Span },
+    ScanWarning { message: String, span: Span },
+    UnitError { message: String, span: Span },
+    RuntimeError { message: String, span: Span },
 }
 
 impl fmt::Display for MyError {
@@ -16,9 +21,41 @@ impl fmt::Display for MyError {
             MyError::FileReadError { path, error } => {
                 write!(f, "Error reading the file '{}': {}", path, error)
             }
-            MyError::ParsingError(msg) => write!(f, "Parsing error: {}", msg),
+            MyError::ScanError { message, span } => {
+                write!(f, "[{}] Error: {}", span, message)
+            }
+            MyError::ScanWarning { message, span } => {
+                write!(f, "[{}] Warning: {}", span, message)
+            }
+            MyError::UnitError { message, span } => {
+                write!(f, "[{}] Unit error: {}", span, message)
+            }
+            MyError::RuntimeError { message, span } => {
+                write!(f, "[{}] Runtime error: {}", span, message)
+            }
         }
     }
 }
 
 impl std::error::Error for MyError {}
+
+/// An error found by the parser, reported at the token where it was detected.
+#[derive(Debug)]
+pub enum ParseError {
+    UnexpectedToken(Box<Token>, String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedToken(token, message) if token.token_type == TokenType::Eof => {
+                write!(f, "[{}] Error at end: {}", token.span(), message)
+            }
+            ParseError::UnexpectedToken(token, message) => {
+                write!(f, "[{}] Error at '{}': {}", token.span(), token.lexeme, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
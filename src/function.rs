@@ -0,0 +1,97 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::stmt::Stmt;
+use crate::environment::Environment;
+use crate::errors::MyError;
+use crate::token::Token;
+use crate::value::Value;
+
+/// A function declared in the program. It keeps the scope it was declared
+/// in, so its body sees the variables around the declaration. Default
+/// values are evaluated once, when the function is declared.
+pub struct Function {
+    pub name: String,
+    pub params: Vec<Token>,
+    pub defaults: Vec<Option<Value>>,
+    pub body: Rc<Vec<Stmt>>,
+    pub closure: Rc<RefCell<Environment>>,
+}
+
+impl Function {
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    /// How many parameters have no default, and so must always be passed.
+    pub fn required(&self) -> usize {
+        self.defaults.iter().filter(|default| default.is_none()).count()
+    }
+
+    /// Matches positional and keyword arguments to the parameters, filling
+    /// the rest from their defaults. Errors point at the offending keyword,
+    /// or at the call's `paren`.
+    pub fn bind(&self, paren: &Token, arguments: Vec<Value>, keywords: Vec<(&Token, Value)>) -> Result<Vec<Value>, MyError> {
+        let count = arguments.len();
+        if count > self.arity() || (keywords.is_empty() && count < self.required()) {
+            return Err(error(paren, self.arity_message(count)));
+        }
+        let mut slots: Vec<Option<Value>> = arguments.into_iter().map(Some).collect();
+        slots.resize(self.arity(), None);
+        for (name, value) in keywords {
+            let Some(position) = self.params.iter().position(|param| param.name() == name.name()) else {
+                return Err(error(name, format!("'{}' has no parameter named '{}'.", self.name, name.name())));
+            };
+            if slots[position].is_some() {
+                return Err(error(name, format!("'{}' got more than one value for '{}'.", self.name, name.name())));
+            }
+            slots[position] = Some(value);
+        }
+        slots
+            .into_iter()
+            .zip(&self.defaults)
+            .zip(&self.params)
+            .map(|((slot, default), param)| {
+                slot.or_else(|| default.clone())
+                    .ok_or_else(|| error(paren, format!("'{}' is missing a value for '{}'.", self.name, param.name())))
+            })
+            .collect()
+    }
+
+    fn arity_message(&self, count: usize) -> String {
+        if self.required() == self.arity() {
+            format!("'{}' expects {} but got {}.", self.name, describe_arguments(self.arity()), count)
+        } else {
+            format!(
+                "'{}' expects {} to {} arguments but got {}.",
+                self.name,
+                self.required(),
+                self.arity(),
+                count
+            )
+        }
+    }
+}
+
+// "1 argument", "2 arguments".
+pub fn describe_arguments(count: usize) -> String {
+    if count == 1 {
+        "1 argument".to_string()
+    } else {
+        format!("{} arguments", count)
+    }
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn {}>", self.name)
+    }
+}
+
+fn error(token: &Token, message: String) -> MyError {
+    MyError::RuntimeError {
+        message,
+        span: token.span(),
+    }
+}
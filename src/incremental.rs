@@ -0,0 +1,233 @@
+use std::ops::Range;
+
+use crate::errors::MyError;
+use crate::scanner::Scanner;
+use crate::token::{Span, Token, TokenType};
+
+/// A change to the source text: the bytes in `range` of the old text are
+/// replaced by `text`.
+#[derive(Debug, Clone)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, text: &str) -> Self {
+        TextEdit {
+            range,
+            text: text.to_string(),
+        }
+    }
+
+    // How far text after the edit moves, in bytes.
+    fn delta(&self) -> isize {
+        self.text.len() as isize - self.range.len() as isize
+    }
+}
+
+impl<'a> Scanner<'a> {
+    /// Updates `old_tokens`, the tokens of the text before `edit`, for the
+    /// edited text this scanner was created over. Only the region around the
+    /// edit is scanned again: once a new token lines up with an old one past
+    /// the edit, the remaining old tokens are reused with their spans moved.
+    ///
+    /// The scanner must have the same configuration and trivia mode as the
+    /// one that produced `old_tokens`. The returned errors and the scanner's
+    /// warnings cover only the re-scanned region.
+    pub fn relex(mut self, old_tokens: &[Token], edit: &TextEdit) -> (Vec<Token>, Vec<MyError>) {
+        let open = open_interpolations(old_tokens);
+        let restart = restart_index(old_tokens, &open, edit.range.start);
+
+        let mut tokens: Vec<Token> = old_tokens[..restart].to_vec();
+        let mut errors = Vec::new();
+        if restart > 0 {
+            let token = &old_tokens[restart];
+            let start = token.leading_trivia().first().map_or(token.span(), |trivia| trivia.span);
+            self = self.resume_at(start);
+        }
+
+        let edit_end = edit.range.start + edit.text.len();
+        let delta = edit.delta();
+        let mut next_old = restart;
+        while let Some(result) = self.next() {
+            let token = match result {
+                Ok(token) => token,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+
+            let start = token.span().start;
+            while next_old < old_tokens.len() && shifted(old_tokens[next_old].span().start, delta) < start as isize {
+                next_old += 1;
+            }
+            let synced = start >= edit_end
+                && !self.in_interpolation()
+                && next_old < old_tokens.len()
+                && open[next_old + 1] == 0
+                && lines_up(&token, &old_tokens[next_old], delta);
+
+            if synced {
+                let old = &old_tokens[next_old];
+                let (old_span, new_span) = (old.span(), token.span());
+                tokens.push(token);
+                for old_token in &old_tokens[next_old + 1..] {
+                    let mut moved = old_token.clone();
+                    moved.map_spans(|span| move_span(span, old_span, new_span));
+                    tokens.push(moved);
+                }
+                break;
+            }
+            tokens.push(token);
+        }
+        (tokens, errors)
+    }
+}
+
+// The number of string interpolations open before each token, plus one entry
+// for the end of the stream. The scanner can only resume where this is zero.
+fn open_interpolations(tokens: &[Token]) -> Vec<usize> {
+    let mut open = Vec::with_capacity(tokens.len() + 1);
+    let mut depth = 0usize;
+    open.push(depth);
+    for token in tokens {
+        match token.token_type {
+            TokenType::Interpolation if token.lexeme.starts_with('"') => depth += 1,
+            TokenType::String if token.lexeme.starts_with('}') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        open.push(depth);
+    }
+    open
+}
+
+// The old token to start scanning from. This is the token before the first
+// one reaching the edit, since text scanned after a token can still change
+// it: `1 m` becomes a single quantity when `m` is typed after `1 `.
+fn restart_index(tokens: &[Token], open: &[usize], edit_start: usize) -> usize {
+    let reaching = tokens
+        .iter()
+        .position(|token| token_end(token) >= edit_start)
+        .unwrap_or(tokens.len());
+    let mut restart = reaching.saturating_sub(1);
+    while restart > 0 && open[restart] > 0 {
+        restart -= 1;
+    }
+    restart
+}
+
+fn token_end(token: &Token) -> usize {
+    token.trailing_trivia().last().map_or(token.span().end, |trivia| trivia.span.end)
+}
+
+fn shifted(offset: usize, delta: isize) -> isize {
+    offset as isize + delta
+}
+
+fn lines_up(new: &Token, old: &Token, delta: isize) -> bool {
+    shifted(old.span().start, delta) == new.span().start as isize
+        && new.token_type == old.token_type
+        && new.lexeme == old.lexeme
+}
+
+// Moves a span that followed `old_anchor` in the old text so that it follows
+// `new_anchor` instead. The text between them is unchanged, so only columns
+// on the anchor's own line shift.
+fn move_span(span: Span, old_anchor: Span, new_anchor: Span) -> Span {
+    let column = if span.line == old_anchor.line {
+        span.column + new_anchor.column - old_anchor.column
+    } else {
+        span.column
+    };
+    Span {
+        start: span.start + new_anchor.start - old_anchor.start,
+        end: span.end + new_anchor.start - old_anchor.start,
+        line: span.line + new_anchor.line - old_anchor.line,
+        column,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSERTIONS: &[&str] = &["", "x", "1", " ", "\n", "m", "^2", "\"", "${", "}", "/*", "*/", "//"];
+
+    fn scanner(text: &str, lossless: bool) -> Scanner<'_> {
+        if lossless {
+            Scanner::lossless(text)
+        } else {
+            Scanner::new(text)
+        }
+    }
+
+    // Every insertion from INSERTIONS, and every deletion of up to three
+    // characters, at every position of `text`.
+    fn every_edit(text: &str) -> Vec<TextEdit> {
+        let mut edits = Vec::new();
+        for (start, _) in text.char_indices().chain([(text.len(), ' ')]) {
+            for insertion in INSERTIONS {
+                edits.push(TextEdit::new(start..start, insertion));
+            }
+            for (length, (end, _)) in text[start..].char_indices().skip(1).chain([(text.len() - start, ' ')]).enumerate() {
+                if length == 3 {
+                    break;
+                }
+                edits.push(TextEdit::new(start..start + end, ""));
+            }
+        }
+        edits
+    }
+
+    fn assert_relex_matches_rescan(text: &str, lossless: bool) {
+        let (old_tokens, _) = scanner(text, lossless).scan_tokens();
+        for edit in every_edit(text) {
+            let mut edited = text.to_string();
+            edited.replace_range(edit.range.clone(), &edit.text);
+            let (relexed, _) = scanner(&edited, lossless).relex(&old_tokens, &edit);
+            let (rescanned, _) = scanner(&edited, lossless).scan_tokens();
+            assert_eq!(
+                format!("{:?}", relexed),
+                format!("{:?}", rescanned),
+                "{:?} in {:?} (lossless: {})",
+                edit,
+                text,
+                lossless
+            );
+        }
+    }
+
+    #[test]
+    fn edits_inside_interpolations() {
+        let text = "var s = \"a ${x + 1} b ${\"${y}\"} c\";\nprint s;";
+        assert_relex_matches_rescan(text, false);
+        assert_relex_matches_rescan(text, true);
+    }
+
+    #[test]
+    fn edits_inside_block_comments() {
+        let text = "a /* c /* d */ e */ b\n// note\nc; /// doc\nd;";
+        assert_relex_matches_rescan(text, false);
+        assert_relex_matches_rescan(text, true);
+    }
+
+    #[test]
+    fn edits_to_unit_suffixes() {
+        let text = "var g = 9.81 m/s^2;\nvar t = 2 s * 3μs;\nprint 1 km*g;";
+        assert_relex_matches_rescan(text, false);
+        assert_relex_matches_rescan(text, true);
+    }
+
+    #[test]
+    fn spans_after_the_edit_move_to_the_new_lines() {
+        let text = "a;\nb;\nc;";
+        let (old_tokens, _) = Scanner::new(text).scan_tokens();
+        let edit = TextEdit::new(1..1, " +\n1");
+        let (tokens, errors) = Scanner::new("a +\n1;\nb;\nc;").relex(&old_tokens, &edit);
+        assert!(errors.is_empty());
+        let c = tokens.iter().find(|token| token.lexeme == "c").unwrap();
+        assert_eq!((c.span().start, c.span().line, c.span().column), (10, 4, 1));
+    }
+}
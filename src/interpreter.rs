@@ -0,0 +1,738 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::ast::expr::{Assign, BinaryExpr, Call, ComplexLiteral, Expr, Grouping, Comprehension, Index, Interpolation, Lambda, LiteralValue, Range, TensorLiteral, UnaryExpr, Variable};
+use crate::ast::stmt::{BlockStmt, ExpressionStmt, FunctionStmt, IfStmt, PrintStmt, ReturnStmt, Stmt, VarStmt, WhileStmt};
+use crate::ast::subscript::Subscript;
+use crate::ast::visitor::{ExprAccept, ExprVisitor, StmtAccept, StmtVisitor};
+use crate::builtins::BUILTINS;
+use crate::complex::Complex;
+use crate::environment::Environment;
+use crate::errors::MyError;
+use crate::function::{describe_arguments, Function};
+use crate::tensor::{describe_item, slice_positions, Selection, Tensor};
+use crate::token::{Token, TokenType};
+use crate::value::{self, Value};
+
+/// Runs programs by walking the AST. Real and complex numbers mix freely: an
+/// operation with a complex operand produces a complex result.
+///
+/// Global variables persist between calls to `interpret`, so the prompt can
+/// run a program one line at a time.
+pub struct Interpreter {
+    environment: RefCell<Rc<RefCell<Environment>>>,
+}
+
+// Why a statement stopped before completing: a `return` unwinding to its
+// call, or a runtime error unwinding the whole program.
+pub enum Unwind {
+    Return(Value),
+    Error(MyError),
+}
+
+impl From<MyError> for Unwind {
+    fn from(error: MyError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        let mut globals = Environment::new();
+        for builtin in BUILTINS {
+            globals.define(builtin.name, Value::Builtin(builtin));
+        }
+        Self {
+            environment: RefCell::new(Rc::new(RefCell::new(globals))),
+        }
+    }
+
+    pub fn interpret(&self, statements: &[Stmt]) -> Result<(), MyError> {
+        for statement in statements {
+            match statement.accept(self) {
+                Ok(()) => {}
+                Err(Unwind::Error(error)) => return Err(error),
+                // The parser rejects `return` outside a function.
+                Err(Unwind::Return(_)) => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn current_environment(&self) -> Rc<RefCell<Environment>> {
+        Rc::clone(&self.environment.borrow())
+    }
+
+    // Runs `statements` in `environment`, restoring the current scope
+    // afterwards even if they return or fail.
+    fn execute_block(&self, statements: &[Stmt], environment: Environment) -> Result<(), Unwind> {
+        let previous = self.environment.replace(Rc::new(RefCell::new(environment)));
+        let result = statements.iter().try_for_each(|statement| statement.accept(self));
+        self.environment.replace(previous);
+        result
+    }
+
+    // Evaluates an index or slice bound, which must be a whole number.
+    fn integer(&self, expr: &Expr, token: &Token, what: &str) -> Result<i64, MyError> {
+        match expr.accept(self)? {
+            Value::Number(x) if x.fract() == 0.0 => Ok(x as i64),
+            Value::Number(x) => Err(runtime_error(token, format!("{} must be an integer but got {}.", what, x))),
+            other => Err(runtime_error(
+                token,
+                format!("{} must be an integer but got {}.", what, other.type_name()),
+            )),
+        }
+    }
+
+    fn number(&self, expr: &Expr, token: &Token, what: &str) -> Result<f64, MyError> {
+        match expr.accept(self)? {
+            Value::Number(x) if x.is_finite() => Ok(x),
+            Value::Number(x) => Err(runtime_error(token, format!("{} must be finite but got {}.", what, x))),
+            other => Err(runtime_error(
+                token,
+                format!("{} must be a number but got {}.", what, other.type_name()),
+            )),
+        }
+    }
+
+    // Runs the generators of a comprehension from `depth` inwards, binding
+    // each variable in turn and collecting the body's values. `lengths`
+    // holds how many values each generator produced the first time it ran;
+    // it must produce as many every time, so the result is not ragged.
+    fn generate(&self, expr: &Comprehension, depth: usize, lengths: &mut Vec<usize>, elements: &mut Elements) -> Result<(), MyError> {
+        let Some((name, iterable)) = expr.generators.get(depth) else {
+            return elements.push(&expr.bracket, expr.body.accept(self)?);
+        };
+        let items = match iterable.accept(self)? {
+            Value::Tensor(tensor) => items(&tensor),
+            other => {
+                return Err(runtime_error(
+                    name,
+                    format!("Can only iterate over a tensor, not {}.", other.type_name()),
+                ));
+            }
+        };
+        match lengths.get(depth) {
+            None => lengths.push(items.len()),
+            Some(&length) if length != items.len() => {
+                return Err(runtime_error(
+                    name,
+                    format!(
+                        "Comprehension is ragged: '{}' has {} here but {} before.",
+                        name.name(),
+                        values(items.len()),
+                        values(length)
+                    ),
+                ));
+            }
+            Some(_) => {}
+        }
+        for item in items {
+            self.current_environment().borrow_mut().define(name.name(), item);
+            self.generate(expr, depth + 1, lengths, elements)?;
+        }
+        Ok(())
+    }
+
+    // A function closing over the current scope, with its defaults
+    // evaluated now.
+    fn function(&self, name: &str, params: &[Token], defaults: &[Option<Expr>], body: &Rc<Vec<Stmt>>) -> Result<Function, MyError> {
+        let defaults = defaults
+            .iter()
+            .map(|default| default.as_ref().map(|default| default.accept(self)).transpose())
+            .collect::<Result<Vec<Option<Value>>, MyError>>()?;
+        Ok(Function {
+            name: name.to_string(),
+            params: params.to_vec(),
+            defaults,
+            body: Rc::clone(body),
+            closure: self.current_environment(),
+        })
+    }
+
+    fn call_function(&self, function: &Function, arguments: Vec<Value>) -> Result<Value, MyError> {
+        let mut environment = Environment::with_enclosing(Rc::clone(&function.closure));
+        for (param, argument) in function.params.iter().zip(arguments) {
+            environment.define(param.name(), argument);
+        }
+        match self.execute_block(&function.body, environment) {
+            Ok(()) => Ok(Value::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(error)) => Err(error),
+        }
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The values of a tensor literal or comprehension, flattened in row-major
+// order. They must all be real numbers, or all tensors of one shape.
+#[derive(Default)]
+struct Elements {
+    shape: Option<Vec<usize>>,
+    data: Vec<f64>,
+}
+
+impl Elements {
+    fn push(&mut self, bracket: &Token, value: Value) -> Result<(), MyError> {
+        let shape = match value {
+            Value::Number(x) => {
+                self.data.push(x);
+                Vec::new()
+            }
+            Value::Tensor(tensor) => {
+                self.data.extend_from_slice(&tensor.data);
+                tensor.shape.clone()
+            }
+            other => {
+                return Err(runtime_error(
+                    bracket,
+                    format!("Tensor elements must be real numbers but got {}.", other.type_name()),
+                ));
+            }
+        };
+        match &self.shape {
+            Some(expected) if *expected != shape => Err(runtime_error(
+                bracket,
+                format!(
+                    "Tensor elements must all have the same shape, but found {} and {}.",
+                    describe_item(expected),
+                    describe_item(&shape)
+                ),
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.shape = Some(shape);
+                Ok(())
+            }
+        }
+    }
+
+    // A tensor whose shape is `outer` followed by the elements' own shape.
+    fn into_tensor(self, outer: &[usize]) -> Value {
+        let shape = [outer, &self.shape.unwrap_or_default()].concat();
+        Value::Tensor(Rc::new(Tensor::new(shape, self.data)))
+    }
+}
+
+// The slices of a tensor along its first axis: numbers for a vector, rows
+// for a matrix, and so on.
+fn items(tensor: &Tensor) -> Vec<Value> {
+    let Some((&length, rest)) = tensor.shape.split_first() else {
+        return vec![Value::Number(tensor.data[0])];
+    };
+    (0..length)
+        .map(|i| {
+            let mut selections = vec![Selection::Single(i)];
+            selections.extend(rest.iter().map(|&n| Selection::Many((0..n).collect())));
+            let item = tensor.select(&selections);
+            if item.rank() == 0 {
+                Value::Number(item.data[0])
+            } else {
+                Value::Tensor(Rc::new(item))
+            }
+        })
+        .collect()
+}
+
+// Applies one of `@ ⊗ • ×`. Real numbers act as tensors with no axes, so
+// `2 ⊗ t` scales `t`. Tensors hold real numbers only, so complex operands
+// are allowed just where both are scalars: `⊗` multiplies them and `•`
+// takes the inner product `conj(a) * b`.
+fn tensor_operation(operator: &Token, left: &Value, right: &Value) -> Result<Value, MyError> {
+    let tensor = |value: &Value| match value {
+        Value::Number(x) => Some(Rc::new(Tensor::new(Vec::new(), vec![*x]))),
+        Value::Tensor(tensor) => Some(Rc::clone(tensor)),
+        _ => None,
+    };
+    let scalar = |value: &Value| matches!(value, Value::Number(_) | Value::Complex(_));
+    match operator.token_type {
+        TokenType::TensorProduct if scalar(left) && scalar(right) => {
+            return arithmetic(operator, left, right, |a, b| a * b, |a, b| a * b);
+        }
+        TokenType::TensorDot if scalar(left) && scalar(right) => {
+            return arithmetic(operator, left, right, |a, b| a * b, |a, b| a.conj() * b);
+        }
+        _ => {}
+    }
+    let (Some(a), Some(b)) = (tensor(left), tensor(right)) else {
+        return Err(runtime_error(
+            operator,
+            format!(
+                "Operands of '{}' must be tensors or real numbers but got {} and {}.",
+                operator.lexeme,
+                left.type_name(),
+                right.type_name()
+            ),
+        ));
+    };
+    let result = match operator.token_type {
+        TokenType::TensorMul => a.matmul(&b),
+        TokenType::TensorProduct => Ok(a.kron(&b)),
+        TokenType::TensorDot => a.dot(&b),
+        _ => a.cross(&b),
+    }
+    .map_err(|message| runtime_error(operator, message))?;
+    if result.rank() == 0 {
+        Ok(Value::Number(result.data[0]))
+    } else {
+        Ok(Value::Tensor(Rc::new(result)))
+    }
+}
+
+// "1 value", "2 values".
+fn values(count: usize) -> String {
+    if count == 1 {
+        "1 value".to_string()
+    } else {
+        format!("{} values", count)
+    }
+}
+
+fn runtime_error(token: &Token, message: String) -> MyError {
+    MyError::RuntimeError {
+        message,
+        span: token.span(),
+    }
+}
+
+// Applies an arithmetic operator, promoting a real operand to complex when
+// the other operand is complex.
+fn arithmetic(
+    operator: &Token,
+    left: &Value,
+    right: &Value,
+    real: fn(f64, f64) -> f64,
+    complex: fn(Complex, Complex) -> Complex,
+) -> Result<Value, MyError> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => Ok(Value::Number(real(*a, *b))),
+        (Value::Number(a), Value::Complex(b)) => Ok(Value::Complex(complex(Complex::from_real(*a), *b))),
+        (Value::Complex(a), Value::Number(b)) => Ok(Value::Complex(complex(*a, Complex::from_real(*b)))),
+        (Value::Complex(a), Value::Complex(b)) => Ok(Value::Complex(complex(*a, *b))),
+        _ => Err(runtime_error(
+            operator,
+            format!(
+                "Operands of '{}' must be numbers but got {} and {}.",
+                operator.lexeme,
+                left.type_name(),
+                right.type_name()
+            ),
+        )),
+    }
+}
+
+fn comparison(operator: &Token, left: &Value, right: &Value, compare: fn(&f64, &f64) -> bool) -> Result<Value, MyError> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => Ok(Value::Bool(compare(a, b))),
+        _ => Err(runtime_error(
+            operator,
+            format!(
+                "Operands of '{}' must be real numbers but got {} and {}.",
+                operator.lexeme,
+                left.type_name(),
+                right.type_name()
+            ),
+        )),
+    }
+}
+
+impl ExprVisitor<Result<Value, MyError>> for Interpreter {
+    fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<Value, MyError> {
+        let operator = &expr.operator;
+        let left = expr.left.accept(self)?;
+
+        // `and` and `or` short-circuit and return the deciding operand.
+        match operator.token_type {
+            TokenType::Or | TokenType::OrOr if left.is_truthy() => return Ok(left),
+            TokenType::And | TokenType::AndAnd if !left.is_truthy() => return Ok(left),
+            TokenType::Or | TokenType::OrOr | TokenType::And | TokenType::AndAnd => {
+                return expr.right.accept(self);
+            }
+            _ => {}
+        }
+
+        let right = expr.right.accept(self)?;
+        match operator.token_type {
+            TokenType::Plus => match (&left, &right) {
+                (Value::String(a), Value::String(b)) => Ok(Value::String(Rc::from(format!("{}{}", a, b)))),
+                _ => arithmetic(operator, &left, &right, |a, b| a + b, |a, b| a + b),
+            },
+            TokenType::Minus => arithmetic(operator, &left, &right, |a, b| a - b, |a, b| a - b),
+            TokenType::Star => arithmetic(operator, &left, &right, |a, b| a * b, |a, b| a * b),
+            TokenType::Slash => arithmetic(operator, &left, &right, |a, b| a / b, |a, b| a / b),
+            TokenType::Caret => arithmetic(operator, &left, &right, f64::powf, Complex::powc),
+            TokenType::Percent => match (&left, &right) {
+                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a % b)),
+                _ => Err(runtime_error(
+                    operator,
+                    format!(
+                        "Operands of '%' must be real numbers but got {} and {}.",
+                        left.type_name(),
+                        right.type_name()
+                    ),
+                )),
+            },
+            TokenType::Less => comparison(operator, &left, &right, f64::lt),
+            TokenType::LessEqual => comparison(operator, &left, &right, f64::le),
+            TokenType::Greater => comparison(operator, &left, &right, f64::gt),
+            TokenType::GreaterEqual => comparison(operator, &left, &right, f64::ge),
+            TokenType::EqualEqual => Ok(Value::Bool(left == right)),
+            TokenType::BangEqual => Ok(Value::Bool(left != right)),
+            TokenType::TensorMul | TokenType::TensorProduct | TokenType::TensorDot | TokenType::TensorCross => {
+                tensor_operation(operator, &left, &right)
+            }
+            _ => Err(runtime_error(
+                operator,
+                format!(
+                    "Operator '{}' is not supported for {} and {}.",
+                    operator.lexeme,
+                    left.type_name(),
+                    right.type_name()
+                ),
+            )),
+        }
+    }
+
+    fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<Value, MyError> {
+        let right = expr.right.accept(self)?;
+        match (expr.operator.token_type, &right) {
+            (TokenType::Bang, _) => Ok(Value::Bool(!right.is_truthy())),
+            (TokenType::Minus, Value::Number(n)) => Ok(Value::Number(-n)),
+            (TokenType::Minus, Value::Complex(z)) => Ok(Value::Complex(-*z)),
+            (TokenType::Plus, Value::Number(_) | Value::Complex(_)) => Ok(right),
+            _ => Err(runtime_error(
+                &expr.operator,
+                format!(
+                    "Operand of '{}' must be a number but got {}.",
+                    expr.operator.lexeme,
+                    right.type_name()
+                ),
+            )),
+        }
+    }
+
+    fn visit_literal_value(&self, expr: &LiteralValue) -> Result<Value, MyError> {
+        Ok(expr.value.clone())
+    }
+
+    fn visit_complex_literal(&self, expr: &ComplexLiteral) -> Result<Value, MyError> {
+        Ok(Value::Complex(Complex::new(expr.real, expr.imaginary)))
+    }
+
+    fn visit_interpolation(&self, expr: &Interpolation) -> Result<Value, MyError> {
+        let values = expr
+            .expressions
+            .iter()
+            .map(|expression| expression.accept(self))
+            .collect::<Result<Vec<Value>, MyError>>()?;
+        Ok(Value::String(Rc::from(value::interpolate(&expr.strings, &values))))
+    }
+
+    // The literal's shape comes from its brackets; elements that evaluate
+    // to tensors add their own dimensions after it.
+    fn visit_tensor_literal(&self, expr: &TensorLiteral) -> Result<Value, MyError> {
+        let mut elements = Elements::default();
+        for element in &expr.elements {
+            let value = match element.accept(self)? {
+                // A row holding one number is a row of one: `[x; y]` is 2×1.
+                Value::Number(x) if expr.rows => Value::Tensor(Rc::new(Tensor::new(vec![1], vec![x]))),
+                value => value,
+            };
+            elements.push(&expr.bracket, value)?;
+        }
+        Ok(elements.into_tensor(&expr.shape))
+    }
+
+    // Indices pick one position and drop their axis; slices keep it. A '...'
+    // stands for as many whole axes as the other subscripts leave, and axes
+    // after the last subscript are taken whole.
+    fn visit_index(&self, expr: &Index) -> Result<Value, MyError> {
+        let tensor = match expr.object.accept(self)? {
+            Value::Tensor(tensor) => tensor,
+            other => {
+                return Err(runtime_error(
+                    &expr.bracket,
+                    format!("Only tensors can be indexed, not {}.", other.type_name()),
+                ));
+            }
+        };
+        let rank = tensor.rank();
+        let explicit = expr
+            .subscripts
+            .iter()
+            .filter(|subscript| !matches!(subscript, Subscript::EllipsisSubscript(_)))
+            .count();
+        if explicit > rank {
+            return Err(runtime_error(
+                &expr.bracket,
+                format!("Too many indices for a tensor of rank {}: got {}.", rank, explicit),
+            ));
+        }
+
+        let whole_axis = |axis: usize| Selection::Many((0..tensor.shape[axis]).collect());
+        let mut selections = Vec::with_capacity(rank);
+        for subscript in &expr.subscripts {
+            let axis = selections.len();
+            match subscript {
+                Subscript::EllipsisSubscript(_) => {
+                    for axis in axis..axis + rank - explicit {
+                        selections.push(whole_axis(axis));
+                    }
+                }
+                Subscript::IndexSubscript(subscript) => {
+                    let length = tensor.shape[axis];
+                    let index = self.integer(&subscript.index, &expr.bracket, "An index")?;
+                    let position = if index < 0 { index + length as i64 } else { index };
+                    if position < 0 || position >= length as i64 {
+                        return Err(runtime_error(
+                            &expr.bracket,
+                            format!("Index {} is out of range for axis {} of length {}.", index, axis, length),
+                        ));
+                    }
+                    selections.push(Selection::Single(position as usize));
+                }
+                Subscript::SliceSubscript(subscript) => {
+                    let bound = |bound: &Option<Expr>, what: &str| {
+                        bound.as_ref().map(|bound| self.integer(bound, &expr.bracket, what)).transpose()
+                    };
+                    let start = bound(&subscript.start, "A slice start")?;
+                    let stop = bound(&subscript.stop, "A slice end")?;
+                    let step = bound(&subscript.step, "A slice step")?;
+                    if step == Some(0) {
+                        return Err(runtime_error(
+                            &expr.bracket,
+                            format!("The slice step for axis {} cannot be zero.", axis),
+                        ));
+                    }
+                    selections.push(Selection::Many(slice_positions(tensor.shape[axis], start, stop, step)));
+                }
+            }
+        }
+        while selections.len() < rank {
+            selections.push(whole_axis(selections.len()));
+        }
+
+        // Selecting everything shares the tensor instead of copying it.
+        if (0..rank).all(|axis| selections[axis] == whole_axis(axis)) {
+            return Ok(Value::Tensor(tensor));
+        }
+        let result = tensor.select(&selections);
+        if result.rank() == 0 {
+            Ok(Value::Number(result.data[0]))
+        } else {
+            Ok(Value::Tensor(Rc::new(result)))
+        }
+    }
+
+    fn visit_grouping(&self, expr: &Grouping) -> Result<Value, MyError> {
+        expr.expression.accept(self)
+    }
+
+    fn visit_variable(&self, expr: &Variable) -> Result<Value, MyError> {
+        self.current_environment().borrow().get(&expr.name)
+    }
+
+    fn visit_assign(&self, expr: &Assign) -> Result<Value, MyError> {
+        let value = expr.value.accept(self)?;
+        self.current_environment().borrow_mut().assign(&expr.name, value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_call(&self, expr: &Call) -> Result<Value, MyError> {
+        let callee = expr.callee.accept(self)?;
+        let arguments = expr
+            .arguments
+            .iter()
+            .map(|argument| argument.accept(self))
+            .collect::<Result<Vec<Value>, MyError>>()?;
+        let keywords = expr
+            .keywords
+            .iter()
+            .map(|(name, argument)| Ok((name, argument.accept(self)?)))
+            .collect::<Result<Vec<(&Token, Value)>, MyError>>()?;
+
+        match callee {
+            Value::Builtin(builtin) => {
+                if let Some((name, _)) = keywords.first() {
+                    return Err(runtime_error(
+                        name,
+                        format!("'{}' doesn't take keyword arguments.", builtin.name),
+                    ));
+                }
+                if arguments.len() != builtin.arity {
+                    return Err(runtime_error(
+                        &expr.paren,
+                        format!(
+                            "'{}' expects {} but got {}.",
+                            builtin.name,
+                            describe_arguments(builtin.arity),
+                            arguments.len()
+                        ),
+                    ));
+                }
+                (builtin.function)(&arguments).map_err(|message| runtime_error(&expr.paren, message))
+            }
+            Value::Function(function) => {
+                let arguments = function.bind(&expr.paren, arguments, keywords)?;
+                self.call_function(&function, arguments)
+            }
+            other => Err(runtime_error(
+                &expr.paren,
+                format!("Can only call functions, not {}.", other.type_name()),
+            )),
+        }
+    }
+
+    fn visit_lambda(&self, expr: &Lambda) -> Result<Value, MyError> {
+        let function = self.function("lambda", &expr.params, &expr.defaults, &expr.body)?;
+        Ok(Value::Function(Rc::new(function)))
+    }
+
+    // `0..n` counts up from 0 and stops before n; `0..=n` includes n if the
+    // steps land on it. A negative step counts down.
+    fn visit_range(&self, expr: &Range) -> Result<Value, MyError> {
+        let start = self.number(&expr.start, &expr.operator, "Range start")?;
+        let end = self.number(&expr.end, &expr.operator, "Range end")?;
+        let step = match &expr.step {
+            Some(step) => self.number(step, &expr.operator, "Range step")?,
+            None => 1.0,
+        };
+        if step == 0.0 {
+            return Err(runtime_error(&expr.operator, "Range step can't be zero.".to_string()));
+        }
+        let inclusive = expr.operator.token_type == TokenType::DotDotEqual;
+        let mut data = Vec::new();
+        loop {
+            let x = start + data.len() as f64 * step;
+            let before_end = if step > 0.0 { x < end } else { x > end };
+            if !(before_end || (inclusive && x == end)) {
+                break;
+            }
+            data.push(x);
+        }
+        Ok(Value::Tensor(Rc::new(Tensor::new(vec![data.len()], data))))
+    }
+
+    fn visit_comprehension(&self, expr: &Comprehension) -> Result<Value, MyError> {
+        let environment = Environment::with_enclosing(self.current_environment());
+        let previous = self.environment.replace(Rc::new(RefCell::new(environment)));
+        let mut lengths = Vec::new();
+        let mut elements = Elements::default();
+        let result = self.generate(expr, 0, &mut lengths, &mut elements);
+        self.environment.replace(previous);
+        result?;
+        // Generators inside an empty one never ran.
+        lengths.resize(expr.generators.len(), 0);
+        Ok(elements.into_tensor(&lengths))
+    }
+}
+
+impl StmtVisitor<Result<(), Unwind>> for Interpreter {
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<(), Unwind> {
+        stmt.expression.accept(self)?;
+        Ok(())
+    }
+
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<(), Unwind> {
+        let value = stmt.expression.accept(self)?;
+        println!("{}", value);
+        Ok(())
+    }
+
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<(), Unwind> {
+        let value = match &stmt.initializer {
+            Some(initializer) => initializer.accept(self)?,
+            None => Value::Nil,
+        };
+        self.current_environment().borrow_mut().define(stmt.name.name(), value);
+        Ok(())
+    }
+
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<(), Unwind> {
+        let environment = Environment::with_enclosing(self.current_environment());
+        self.execute_block(&stmt.statements, environment)
+    }
+
+    fn visit_if_stmt(&self, stmt: &IfStmt) -> Result<(), Unwind> {
+        if stmt.condition.accept(self)?.is_truthy() {
+            stmt.then_branch.accept(self)
+        } else if let Some(else_branch) = &stmt.else_branch {
+            else_branch.accept(self)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn visit_while_stmt(&self, stmt: &WhileStmt) -> Result<(), Unwind> {
+        while stmt.condition.accept(self)?.is_truthy() {
+            stmt.body.accept(self)?;
+        }
+        Ok(())
+    }
+
+    fn visit_function_stmt(&self, stmt: &FunctionStmt) -> Result<(), Unwind> {
+        let function = self.function(stmt.name.name(), &stmt.params, &stmt.defaults, &stmt.body)?;
+        self.current_environment()
+            .borrow_mut()
+            .define(stmt.name.name(), Value::Function(Rc::new(function)));
+        Ok(())
+    }
+
+    fn visit_return_stmt(&self, stmt: &ReturnStmt) -> Result<(), Unwind> {
+        let value = match &stmt.value {
+            Some(value) => value.accept(self)?,
+            None => Value::Nil,
+        };
+        Err(Unwind::Return(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    // Runs every statement but the last, which must be an expression, and
+    // returns the last one's shape.
+    fn shape_of(source: &str) -> Vec<usize> {
+        let (tokens, errors) = Scanner::new(source).scan_tokens();
+        assert!(errors.is_empty(), "scan errors: {:?}", errors);
+        let (mut statements, errors) = Parser::new(tokens).parse();
+        assert!(errors.is_empty(), "parse errors: {:?}", errors);
+        let Some(Stmt::ExpressionStmt(last)) = statements.pop() else {
+            panic!("{:?} does not end in an expression", source);
+        };
+        let interpreter = Interpreter::new();
+        interpreter.interpret(&statements).unwrap();
+        match last.expression.accept(&interpreter) {
+            Ok(Value::Tensor(tensor)) => tensor.shape.clone(),
+            Ok(other) => panic!("expected a tensor but got {}", other.type_name()),
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    #[test]
+    fn literal_shapes() {
+        assert_eq!(shape_of("[1, 2; 3, 4];"), [2, 2]);
+        assert_eq!(shape_of("[[1, 2]; [3, 4]];"), [2, 2]);
+        assert_eq!(shape_of("[1; 2];"), [2, 1]);
+        assert_eq!(shape_of("[[1, 2; 3, 4]; [5, 6; 7, 8]];"), [2, 2, 2]);
+    }
+
+    #[test]
+    fn variables_add_their_shape_when_run() {
+        let vectors = "var v = [1, 2]; var w = [3, 4];";
+        assert_eq!(shape_of(&format!("{} [[1, 2], v];", vectors)), [2, 2]);
+        assert_eq!(shape_of(&format!("{} [v; w];", vectors)), [2, 2]);
+        assert_eq!(shape_of(&format!("{} [[1, 2]; w];", vectors)), [2, 2]);
+        assert_eq!(shape_of(&format!("{} [[v]; [w]];", vectors)), [2, 1, 2]);
+        assert_eq!(shape_of("var x = 1; [x; 2];"), [2, 1]);
+        assert_eq!(shape_of("[[1, 2], [i for i in 0..2]];"), [2, 2]);
+    }
+}
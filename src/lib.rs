@@ -0,0 +1,16 @@
+pub mod token;
+pub mod scanner;
+pub mod scanner_config;
+pub mod incremental;
+pub mod parser;
+pub mod ast;
+pub mod dimensions;
+pub mod interpreter;
+pub mod environment;
+pub mod function;
+pub mod errors;
+pub mod complex;
+pub mod value;
+pub mod tensor;
+pub mod builtins;
+pub mod units;
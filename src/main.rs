@@ -1,18 +1,19 @@
-mod scanner;
-mod errors;
-
-use crate :: scanner::*;
-use crate :: errors::MyError;
+use ci :: scanner::*;
+use ci :: errors::MyError;
+use ci :: parser::Parser;
+use ci :: interpreter::Interpreter;
+use ci :: dimensions::check_dimensions;
 
 use std::io::{self, Write};
-use std::{env, fs, vec};
+use std::{env, fs};
 use std::cell::Cell;
 use std::process::exit;
 use std::error::Error;
 
 
 thread_local! {
-    static HAD_ERROR: Cell<bool> = Cell::new(false);
+    static HAD_ERROR: Cell<bool> = const { Cell::new(false) };
+    static HAD_RUNTIME_ERROR: Cell<bool> = const { Cell::new(false) };
 }
 
 
@@ -25,34 +26,68 @@ fn run_file(path: &str) -> Result<(),MyError> {
         path: path.to_string(),
         error: e,
     })?;
-    println!("File contents: {}", contents);
-    run(&contents)?;
+    run(&contents, &Interpreter::new());
+    if HAD_ERROR.with(|had_error| had_error.get()) {
+        exit(65);
+    }
+    if HAD_RUNTIME_ERROR.with(|had_error| had_error.get()) {
+        exit(70);
+    }
     Ok(())
 
 }
 
 
-fn run(_contents: &str) -> Result<Vec<Token>, MyError> {
-    let mut scanner = Scanner::new(_contents);
+// Reports a compile-time error; the program is not run.
+fn report(error: &dyn Error) {
+    eprintln!("{}", error);
+    HAD_ERROR.with(|had_error| had_error.set(true));
+}
+
+// Scans, parses and checks the source, then runs it.
+fn run(contents: &str, interpreter: &Interpreter) {
+    let mut scanner = Scanner::new(contents);
     
-    // Scan tokens and handle potential errors
-    match scanner.scan_tokens() {
-        Ok(tokens) => {
-            for token in &tokens {
-                println!("Token: {:?}", token);
-            }
-            Ok(tokens)
+    // Scan tokens, reporting every lexical error rather than just the first
+    let (tokens, errors) = scanner.scan_tokens();
+    for warning in scanner.take_warnings() {
+        eprintln!("{}", warning);
+    }
+    if !errors.is_empty() {
+        for error in &errors {
+            report(error);
         }
-        Err(e) => {
-           
-            Err(e)
+        return;
+    }
+
+    // Parse the whole program, reporting every syntax error found
+    let (statements, parse_errors) = Parser::new(tokens).parse();
+    if !parse_errors.is_empty() {
+        for error in &parse_errors {
+            report(error);
+        }
+        return;
+    }
+
+    let unit_errors = check_dimensions(&statements);
+    if !unit_errors.is_empty() {
+        for error in &unit_errors {
+            report(error);
         }
+        return;
+    }
+
+    if let Err(error) = interpreter.interpret(&statements) {
+        eprintln!("{}", error);
+        HAD_RUNTIME_ERROR.with(|had_error| had_error.set(true));
     }
 }
 
 // Function to handle the interactive prompt
 fn run_prompt() -> Result<(), Box<dyn Error>> {
     let stdin = io::stdin();
+    // Shared by every line, so declarations carry over to the next one.
+    let interpreter = Interpreter::new();
 
     loop {
         let mut buffer = String::new();
@@ -73,9 +108,9 @@ fn run_prompt() -> Result<(), Box<dyn Error>> {
 
         println!("You wrote: {}", input);
         
-        if let Err(e) = run(input) {
-            eprintln!("Error: {}", e);
-        }
+        run(input, &interpreter);
+        HAD_ERROR.with(|had_error| had_error.set(false));
+        HAD_RUNTIME_ERROR.with(|had_error| had_error.set(false));
     }
 
     Ok(())
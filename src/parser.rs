@@ -1,197 +1,773 @@
-use crate::expr::*;
-use crate::token::*;
+use std::rc::Rc;
+
+use crate::ast::expr::*;
+use crate::ast::stmt::*;
+use crate::ast::subscript::*;
+use crate::errors::ParseError;
+use crate::tensor::{describe_item, format_shape};
+use crate::token::{self, Token, TokenType};
+use crate::value::Value;
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    // How many function bodies enclose the current token; `return` is only
+    // allowed inside one.
+    function_depth: usize,
+    // How many blocks enclose the current token, so that recovery stops at
+    // a '}' that closes one.
+    block_depth: usize,
+    errors: Vec<ParseError>,
 }
 
 impl Parser {
+    /// Doc comments are kept by the scanner for documentation tools but are
+    /// not part of the grammar, so the parser never sees them.
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        let tokens = tokens
+            .into_iter()
+            .filter(|token| token.token_type != TokenType::DocComment)
+            .collect();
+        Self {
+            tokens,
+            current: 0,
+            function_depth: 0,
+            block_depth: 0,
+            errors: Vec::new(),
+        }
     }
 
-    fn parse_expression(&mut self) -> Result<Expr, ParseError> {
-        self.parse_logical_or()
+    /// Parses the whole token stream as a program: a list of declarations.
+    /// After a syntax error the parser skips to the next statement and keeps
+    /// going, so every error in the program is returned, not just the first.
+    pub fn parse(&mut self) -> (Vec<Stmt>, Vec<ParseError>) {
+        let mut statements = Vec::new();
+        while !self.is_at_end() {
+            if let Some(statement) = self.parse_declaration_or_recover() {
+                statements.push(statement);
+            }
+        }
+        (statements, std::mem::take(&mut self.errors))
     }
 
-    fn parse_logical_or(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.parse_logical_and()?;
-        while let Some(TokenType::Or) = self.peek().map(|t| &t.token_type) {
-            self.advance();
-            let right = self.parse_logical_and()?;
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                operator: TokenType::Or,
-                right: Box::new(right),
-            };
+    // A declaration, or None after recording its error and skipping past it.
+    fn parse_declaration_or_recover(&mut self) -> Option<Stmt> {
+        let start = self.current;
+        match self.parse_declaration() {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                self.errors.push(error);
+                // A declaration that failed on its first token must skip it,
+                // or the next attempt would fail on it again.
+                if self.current == start {
+                    self.advance();
+                }
+                self.synchronize();
+                None
+            }
         }
-        Ok(expr)
     }
 
-    fn parse_logical_and(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.parse_equality()?;
-        while let Some(TokenType::And) = self.peek().map(|t| &t.token_type) {
+    // Panic-mode recovery: discards tokens until the end of the statement
+    // that failed, which is just after a ';', just before a '}' that closes
+    // the enclosing block, or just before a token that starts a statement.
+    // A block opened by the discarded tokens is discarded whole, so that in
+    // `if (1 +) { ... } else { ... }` neither block is parsed on its own.
+    fn synchronize(&mut self) {
+        if self.current > 0 && self.previous().token_type == TokenType::Semicolon {
+            return;
+        }
+        let mut skipped_blocks = 0;
+        while !self.is_at_end() {
+            match self.peek().map(|t| t.token_type) {
+                Some(TokenType::LeftBrace) => skipped_blocks += 1,
+                Some(TokenType::RightBrace) if skipped_blocks > 0 => skipped_blocks -= 1,
+                _ if skipped_blocks > 0 => {}
+                Some(TokenType::RightBrace) if self.block_depth > 0 => return,
+                Some(
+                    TokenType::Class
+                    | TokenType::Fun
+                    | TokenType::Var
+                    | TokenType::For
+                    | TokenType::If
+                    | TokenType::While
+                    | TokenType::Print
+                    | TokenType::Return,
+                ) => return,
+                _ => {}
+            }
+            if self.advance().token_type == TokenType::Semicolon && skipped_blocks == 0 {
+                return;
+            }
+        }
+    }
+
+    // `fun` followed by a name declares a function; followed by '(' it
+    // starts a lambda, which is an expression statement like any other.
+    fn parse_declaration(&mut self) -> Result<Stmt, ParseError> {
+        if self.check(&TokenType::Fun) && !self.check_next(&TokenType::LeftParen) {
             self.advance();
-            let right = self.parse_equality()?;
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                operator: TokenType::And,
-                right: Box::new(right),
-            };
+            self.parse_function_declaration()
+        } else if self.match_any(&[TokenType::Var]) {
+            self.parse_var_declaration()
+        } else {
+            self.parse_statement()
         }
-        Ok(expr)
     }
 
-    fn parse_equality(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.parse_comparison()?;
-        while let Some(op) = self.peek().map(|t| &t.token_type) {
-            match op {
-                TokenType::EqualEqual | TokenType::BangEqual => {
-                    self.advance();
-                    let right = self.parse_comparison()?;
-                    expr = Expr::Binary {
-                        left: Box::new(expr),
-                        operator: op.clone(),
-                        right: Box::new(right),
-                    };
+    fn parse_function_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(TokenType::Identifier, "Expect function name.")?.clone();
+        self.consume(TokenType::LeftParen, "Expect '(' after function name.")?;
+        let (params, defaults) = self.parse_parameters()?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before function body.")?;
+        let body = self.parse_function_body(|parser| parser.parse_block())?;
+        Ok(Stmt::FunctionStmt(Box::new(FunctionStmt {
+            name,
+            params,
+            defaults,
+            body,
+        })))
+    }
+
+    // The parameters of a function or lambda, after the '(' and up to and
+    // including the ')'. Each may have a default, but once one does, the
+    // rest must too.
+    fn parse_parameters(&mut self) -> Result<(Vec<Token>, Vec<Option<Expr>>), ParseError> {
+        let mut params = Vec::new();
+        let mut defaults = Vec::new();
+        while !self.check(&TokenType::RightParen) {
+            let param = self.consume(TokenType::Identifier, "Expect parameter name.")?.clone();
+            if params.len() == MAX_ARGUMENTS {
+                self.errors.push(ParseError::UnexpectedToken(
+                    Box::new(param.clone()),
+                    format!("Can't have more than {} parameters.", MAX_ARGUMENTS),
+                ));
+            }
+            let default = if self.match_any(&[TokenType::Equal]) {
+                Some(self.parse_expression()?)
+            } else {
+                if defaults.iter().any(Option::is_some) {
+                    self.errors.push(ParseError::UnexpectedToken(
+                        Box::new(param.clone()),
+                        "A parameter without a default can't follow one with a default.".to_string(),
+                    ));
                 }
-                _ => break,
+                None
+            };
+            params.push(param);
+            defaults.push(default);
+            if !self.match_any(&[TokenType::Comma]) {
+                break;
             }
         }
-        Ok(expr)
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+        Ok((params, defaults))
     }
 
-    fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.parse_term()?;
-        while let Some(op) = self.peek().map(|t| &t.token_type) {
-            match op {
-                TokenType::Less | TokenType::LessEqual | TokenType::Greater | TokenType::GreaterEqual => {
-                    self.advance();
-                    let right = self.parse_term()?;
-                    expr = Expr::Binary {
-                        left: Box::new(expr),
-                        operator: op.clone(),
-                        right: Box::new(right),
-                    };
-                }
-                _ => break,
+    // Runs `parse` on a function body, where `return` is allowed.
+    fn parse_function_body(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Vec<Stmt>, ParseError>,
+    ) -> Result<Rc<Vec<Stmt>>, ParseError> {
+        self.function_depth += 1;
+        let body = parse(self);
+        self.function_depth -= 1;
+        Ok(Rc::new(body?))
+    }
+
+    fn parse_var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(TokenType::Identifier, "Expect variable name.")?.clone();
+        let initializer = if self.match_any(&[TokenType::Equal]) {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after variable declaration.")?;
+        Ok(Stmt::VarStmt(Box::new(VarStmt { name, initializer })))
+    }
+
+    fn parse_statement(&mut self) -> Result<Stmt, ParseError> {
+        match self.peek().map(|t| t.token_type) {
+            Some(TokenType::Print) => {
+                self.advance();
+                let expression = self.parse_expression()?;
+                self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+                Ok(Stmt::PrintStmt(Box::new(PrintStmt { expression })))
+            }
+            Some(TokenType::LeftBrace) => {
+                self.advance();
+                let statements = self.parse_block()?;
+                Ok(Stmt::BlockStmt(Box::new(BlockStmt { statements })))
+            }
+            Some(TokenType::If) => self.parse_if_statement(),
+            Some(TokenType::While) => self.parse_while_statement(),
+            Some(TokenType::For) => self.parse_for_statement(),
+            Some(TokenType::Return) => self.parse_return_statement(),
+            _ => {
+                let expression = self.parse_expression()?;
+                self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+                Ok(Stmt::ExpressionStmt(Box::new(ExpressionStmt { expression })))
             }
         }
-        Ok(expr)
     }
 
-    fn parse_term(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.parse_factor()?;
-        while let Some(op) = self.peek().map(|t| &t.token_type) {
-            match op {
-                TokenType::Plus | TokenType::Minus => {
-                    self.advance();
-                    let right = self.parse_factor()?;
-                    expr = Expr::Binary {
-                        left: Box::new(expr),
-                        operator: op.clone(),
-                        right: Box::new(right),
-                    };
-                }
-                _ => break,
+    // The statements of a block whose '{' has been consumed.
+    fn parse_block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements = Vec::new();
+        self.block_depth += 1;
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            if let Some(statement) = self.parse_declaration_or_recover() {
+                statements.push(statement);
             }
         }
+        self.block_depth -= 1;
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn parse_if_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.advance();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.parse_expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+        let then_branch = self.parse_statement()?;
+        let else_branch = if self.match_any(&[TokenType::Else]) {
+            Some(self.parse_statement()?)
+        } else {
+            None
+        };
+        Ok(Stmt::IfStmt(Box::new(IfStmt {
+            condition,
+            then_branch,
+            else_branch,
+        })))
+    }
+
+    fn parse_while_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.advance();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.parse_expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        let body = self.parse_statement()?;
+        Ok(Stmt::WhileStmt(Box::new(WhileStmt { condition, body })))
+    }
+
+    // `for` has no node of its own: `for (init; cond; step) body` becomes
+    // `{ init; while (cond) { body step; } }`.
+    fn parse_for_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.advance().clone();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+        let initializer = if self.match_any(&[TokenType::Semicolon]) {
+            None
+        } else if self.match_any(&[TokenType::Var]) {
+            Some(self.parse_var_declaration()?)
+        } else {
+            let expression = self.parse_expression()?;
+            self.consume(TokenType::Semicolon, "Expect ';' after loop initializer.")?;
+            Some(Stmt::ExpressionStmt(Box::new(ExpressionStmt { expression })))
+        };
+        let condition = if self.check(&TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+        let increment = if self.check(&TokenType::RightParen) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let mut body = self.parse_statement()?;
+        if let Some(expression) = increment {
+            let increment = Stmt::ExpressionStmt(Box::new(ExpressionStmt { expression }));
+            body = Stmt::BlockStmt(Box::new(BlockStmt {
+                statements: vec![body, increment],
+            }));
+        }
+        let condition = condition.unwrap_or_else(|| {
+            let token = Token::new(TokenType::True, "true".to_string(), None, keyword.span());
+            literal(token)
+        });
+        body = Stmt::WhileStmt(Box::new(WhileStmt { condition, body }));
+        if let Some(initializer) = initializer {
+            body = Stmt::BlockStmt(Box::new(BlockStmt {
+                statements: vec![initializer, body],
+            }));
+        }
+        Ok(body)
+    }
+
+    fn parse_return_statement(&mut self) -> Result<Stmt, ParseError> {
+        // Not a reason to stop parsing, since the statement itself is fine.
+        if self.function_depth == 0 {
+            let error = self.error("Can't return from top-level code.");
+            self.errors.push(error);
+        }
+        let keyword = self.advance().clone();
+        let value = if self.check(&TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+        Ok(Stmt::ReturnStmt(Box::new(ReturnStmt { keyword, value })))
+    }
+
+    fn parse_expression(&mut self) -> Result<Expr, ParseError> {
+        self.parse_assignment()
+    }
+
+    // Assignment is right-associative, and its target is parsed as an
+    // ordinary expression first so that `a = b = c` needs no lookahead.
+    fn parse_assignment(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.parse_binary(0)?;
+        if self.match_any(&[TokenType::Equal]) {
+            let equals = self.previous().clone();
+            let value = self.parse_assignment()?;
+            return match expr {
+                Expr::Variable(variable) => Ok(Expr::Assign(Box::new(Assign {
+                    name: variable.name,
+                    value,
+                }))),
+                // The parser is not confused, so report without recovering.
+                _ => {
+                    self.errors.push(ParseError::UnexpectedToken(Box::new(equals), "Invalid assignment target.".to_string()));
+                    Ok(expr)
+                }
+            };
+        }
         Ok(expr)
     }
 
-    fn parse_factor(&mut self) -> Result<Expr, ParseError> {
+    // Precedence climbing: parses operands and every infix operator that
+    // binds at least as tightly as `min_power`. A left-associative operator
+    // parses its right operand one level tighter, so `a - b - c` groups as
+    // `(a - b) - c`; a right-associative one at its own level, so `a ^ b ^ c`
+    // groups as `a ^ (b ^ c)`.
+    fn parse_binary(&mut self, min_power: u8) -> Result<Expr, ParseError> {
         let mut expr = self.parse_unary()?;
-        while let Some(op) = self.peek().map(|t| &t.token_type) {
-            match op {
-                TokenType::Star | TokenType::Slash | TokenType::Percent => {
-                    self.advance();
-                    let right = self.parse_unary()?;
-                    expr = Expr::Binary {
-                        left: Box::new(expr),
-                        operator: op.clone(),
-                        right: Box::new(right),
+        while let Some((power, associativity)) = self.peek().and_then(|t| infix_binding(t.token_type)) {
+            if power < min_power {
+                break;
+            }
+            let operator = self.advance().clone();
+            let right_power = match associativity {
+                Associativity::Left => power + 1,
+                Associativity::Right => power,
+            };
+            let right = self.parse_binary(right_power)?;
+            expr = match operator.token_type {
+                TokenType::Pipe => pipeline(expr, operator, right),
+                TokenType::DotDot | TokenType::DotDotEqual => {
+                    let step = if self.match_word("step") {
+                        Some(self.parse_binary(right_power)?)
+                    } else {
+                        None
                     };
+                    Expr::Range(Box::new(Range {
+                        start: expr,
+                        operator,
+                        end: right,
+                        step,
+                    }))
                 }
-                _ => break,
-            }
+                _ => binary(expr, operator, right),
+            };
         }
         Ok(expr)
     }
 
+    // Prefix operators bind tighter than the tensor operators, so `-a @ b`
+    // is `(-a) @ b`, but looser than `^`, so `-2 ^ 2` is `-(2 ^ 2)`.
     fn parse_unary(&mut self) -> Result<Expr, ParseError> {
-        match self.peek().map(|t| &t.token_type) {
-            Some(TokenType::Bang) | Some(TokenType::Minus) | Some(TokenType::Plus) => {
-                let operator = self.advance().token_type.clone();
-                let right = self.parse_unary()?;
-                Ok(Expr::Unary {
-                    operator,
-                    right: Box::new(right),
-                })
-            }
-            _ => self.parse_tensor_op(),
+        if self.match_any(&[TokenType::Bang, TokenType::Minus, TokenType::Plus]) {
+            let operator = self.previous().clone();
+            let right = self.parse_binary(PREFIX_POWER)?;
+            return Ok(Expr::UnaryExpr(Box::new(UnaryExpr { operator, right })));
         }
+        self.parse_postfix()
     }
 
-    fn parse_tensor_op(&mut self) -> Result<Expr, ParseError> {
+    // Calls and indexing bind tighter than any operator and chain from left
+    // to right: `a @ b[0]` indexes `b`, and `f(a)(b)` calls what `f(a)`
+    // returns.
+    fn parse_postfix(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.parse_primary()?;
-        while let Some(op) = self.peek().map(|t| &t.token_type) {
-            match op {
-                TokenType::TensorMul | TokenType::TensorDot | TokenType::TensorCross => {
-                    self.advance();
-                    let right = self.parse_primary()?;
-                    expr = Expr::TensorOp {
-                        left: Box::new(expr),
-                        operator: op.clone(),
-                        right: Box::new(right),
-                    };
+        loop {
+            if self.match_any(&[TokenType::LeftParen]) {
+                expr = self.parse_call(expr)?;
+            } else if self.match_any(&[TokenType::LeftBracket]) {
+                expr = self.parse_index(expr)?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    // The arguments of `conv(x, kernel, stride: 2)`, after the '('. Keyword
+    // arguments come after the positional ones, and a trailing comma is
+    // allowed.
+    fn parse_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let paren = self.previous().clone();
+        let mut arguments = Vec::new();
+        let mut keywords: Vec<(Token, Expr)> = Vec::new();
+        while !self.check(&TokenType::RightParen) {
+            if arguments.len() + keywords.len() == MAX_ARGUMENTS {
+                self.errors.push(self.error(&format!("Can't have more than {} arguments.", MAX_ARGUMENTS)));
+            }
+            if self.check(&TokenType::Identifier) && self.check_next(&TokenType::Colon) {
+                let name = self.advance().clone();
+                self.advance();
+                if keywords.iter().any(|(other, _)| other.name() == name.name()) {
+                    self.errors.push(ParseError::UnexpectedToken(
+                        Box::new(name.clone()),
+                        format!("Argument '{}' is given more than once.", name.name()),
+                    ));
+                }
+                keywords.push((name, self.parse_expression()?));
+            } else {
+                if !keywords.is_empty() {
+                    self.errors.push(self.error("Positional arguments can't follow keyword arguments."));
                 }
-                _ => break,
+                arguments.push(self.parse_expression()?);
+            }
+            if !self.match_any(&[TokenType::Comma]) {
+                break;
             }
         }
-        Ok(expr)
+        self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+        Ok(Expr::Call(Box::new(Call {
+            callee,
+            paren,
+            arguments,
+            keywords,
+        })))
+    }
+
+    // The subscripts of `t[i, 1:3, ...]`, after the '['.
+    fn parse_index(&mut self, object: Expr) -> Result<Expr, ParseError> {
+        let bracket = self.previous().clone();
+        let mut subscripts = Vec::new();
+        let mut has_ellipsis = false;
+        loop {
+            if self.match_any(&[TokenType::DotDotDot]) {
+                let token = self.previous().clone();
+                if has_ellipsis {
+                    let error = ParseError::UnexpectedToken(Box::new(token.clone()), "An index can contain only one '...'.".to_string());
+                    self.errors.push(error);
+                }
+                has_ellipsis = true;
+                subscripts.push(Subscript::EllipsisSubscript(Box::new(EllipsisSubscript { token })));
+            } else {
+                subscripts.push(self.parse_subscript()?);
+            }
+            if !self.match_any(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+        Ok(Expr::Index(Box::new(Index {
+            object,
+            bracket,
+            subscripts,
+        })))
+    }
+
+    // An index `i`, or a slice `start:stop:step` in which every part is
+    // optional, so `:` selects a whole axis and `::2` every other element.
+    fn parse_subscript(&mut self) -> Result<Subscript, ParseError> {
+        let start = if self.check(&TokenType::Colon) {
+            None
+        } else {
+            let index = self.parse_expression()?;
+            if !self.check(&TokenType::Colon) {
+                return Ok(Subscript::IndexSubscript(Box::new(IndexSubscript { index })));
+            }
+            Some(index)
+        };
+        self.advance();
+        let stop = self.parse_slice_bound()?;
+        let step = if self.match_any(&[TokenType::Colon]) {
+            self.parse_slice_bound()?
+        } else {
+            None
+        };
+        Ok(Subscript::SliceSubscript(Box::new(SliceSubscript { start, stop, step })))
+    }
+
+    fn parse_slice_bound(&mut self) -> Result<Option<Expr>, ParseError> {
+        if self.check(&TokenType::Colon) || self.check(&TokenType::Comma) || self.check(&TokenType::RightBracket) {
+            Ok(None)
+        } else {
+            Ok(Some(self.parse_expression()?))
+        }
     }
 
     fn parse_primary(&mut self) -> Result<Expr, ParseError> {
-        match self.peek().map(|t| &t.token_type) {
+        if self.peek().is_some_and(continues_interpolation) {
+            return Err(self.error("Expected expression."));
+        }
+        match self.peek().map(|t| t.token_type) {
             Some(TokenType::Number) | Some(TokenType::String) | Some(TokenType::True) | Some(TokenType::False) | Some(TokenType::Nil) => {
-                Ok(Expr::Literal(self.advance().clone()))
+                let token = self.advance().clone();
+                Ok(literal(token))
             }
+            Some(TokenType::Interpolation) => self.parse_interpolation(),
             Some(TokenType::LeftParen) => {
                 self.advance();
-                let expr = self.parse_expression()?;
+                let expression = self.parse_expression()?;
                 self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
-                Ok(Expr::Grouping(Box::new(expr)))
+                Ok(Expr::Grouping(Box::new(Grouping { expression })))
             }
             Some(TokenType::Identifier) => {
-                self.advance();
-                if self.peek().map(|t| &t.token_type) == Some(&TokenType::LeftParen) {
-                    self.parse_function_call()
+                let name = self.advance().clone();
+                Ok(Expr::Variable(Box::new(Variable { name })))
+            }
+            // Math functions such as `sin` are plain identifiers here, called
+            // like any other function and resolved to builtins at run time.
+            Some(TokenType::LeftBracket) => self.parse_tensor_or_matrix_or_vector_literal(),
+            Some(TokenType::Fun) => self.parse_lambda(),
+            _ => Err(self.error("Expected expression.")),
+        }
+    }
+
+    // `fun (x) => x * 2` returns the expression after the arrow, and
+    // `fun (x) { ... }` has a block body like a declared function. Either
+    // way the lambda captures the scope it is evaluated in.
+    fn parse_lambda(&mut self) -> Result<Expr, ParseError> {
+        let keyword = self.advance().clone();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'fun'.")?;
+        let (params, defaults) = self.parse_parameters()?;
+        let body = if self.match_any(&[TokenType::FatArrow]) {
+            let arrow = self.previous().clone();
+            self.parse_function_body(|parser| {
+                let value = parser.parse_expression()?;
+                Ok(vec![Stmt::ReturnStmt(Box::new(ReturnStmt {
+                    keyword: arrow,
+                    value: Some(value),
+                }))])
+            })?
+        } else {
+            self.consume(TokenType::LeftBrace, "Expect '=>' or '{' after lambda parameters.")?;
+            self.parse_function_body(|parser| parser.parse_block())?
+        };
+        Ok(Expr::Lambda(Box::new(Lambda {
+            keyword,
+            params,
+            defaults,
+            body,
+        })))
+    }
+
+    // `[1, 2, 3]` is a vector and `[1, 2; 3, 4]` a matrix, with ';' between
+    // rows. Nesting adds dimensions: `[[1, 2], [3, 4]]` is also a 2×2 matrix
+    // and `[[1, 2; 3, 4]; [5, 6; 7, 8]]` has shape 2×2×2. When every item's
+    // shape is known while parsing, the elements of nested literals are
+    // flattened into one row-major list. Otherwise each item stays whole and
+    // the interpreter stacks the items it evaluates to.
+    fn parse_tensor_or_matrix_or_vector_literal(&mut self) -> Result<Expr, ParseError> {
+        let bracket = self.advance().clone();
+        let mut rows = Vec::new();
+        let mut separated = false;
+        if !self.check(&TokenType::RightBracket) {
+            let start = self.peek().cloned().unwrap_or_else(|| self.previous().clone());
+            let first = self.parse_expression()?;
+            if self.match_any(&[TokenType::For]) {
+                return self.parse_comprehension(bracket, first);
+            }
+            let mut first_item = Some((start, first));
+            loop {
+                rows.push(self.parse_tensor_row(first_item.take())?);
+                if !self.match_any(&[TokenType::Semicolon]) {
+                    break;
+                }
+                separated = true;
+            }
+        }
+        self.consume(TokenType::RightBracket, "Expect ']' after tensor elements.")?;
+
+        let shapes: Vec<Option<Vec<usize>>> = rows.iter().map(|row| row.shape(separated)).collect();
+        let mut first_known: Option<(usize, &Vec<usize>)> = None;
+        for (i, shape) in shapes.iter().enumerate() {
+            let Some(shape) = shape else { continue };
+            match first_known {
+                None => first_known = Some((i, shape)),
+                Some((first, expected)) if expected != shape => {
+                    let message = format!(
+                        "Ragged rows in tensor literal: row {} has {} but row {} has {}.",
+                        first + 1,
+                        describe_shape(expected),
+                        i + 1,
+                        describe_shape(shape)
+                    );
+                    self.errors.push(ParseError::UnexpectedToken(Box::new(rows[i].items[0].0.clone()), message));
+                }
+                Some(_) => {}
+            }
+        }
+
+        let (shape, elements, rows) = match (first_known, shapes.iter().all(Option::is_some)) {
+            (None, _) if rows.is_empty() => (vec![0], Vec::new(), false),
+            (Some((_, row_shape)), true) => {
+                let shape = if separated {
+                    [&[rows.len()][..], row_shape].concat()
                 } else {
-                    Ok(Expr::Variable(self.previous().clone()))
+                    row_shape.clone()
+                };
+                let mut elements = Vec::new();
+                for (_, item) in rows.into_iter().flat_map(|row| row.items) {
+                    match item {
+                        Expr::TensorLiteral(literal) => elements.extend(literal.elements),
+                        element => elements.push(element),
+                    }
                 }
+                (shape, elements, false)
+            }
+            // A row holding just one item is that item, so `[v; w]` stacks
+            // two vectors into a matrix and `[x; y]` with numbers is 2×1.
+            _ if separated => {
+                let shape = vec![rows.len()];
+                let elements = rows
+                    .into_iter()
+                    .map(|row| match <[_; 1]>::try_from(row.items) {
+                        Ok([(_, item)]) => item,
+                        Err(items) => Expr::TensorLiteral(Box::new(TensorLiteral {
+                            bracket: bracket.clone(),
+                            shape: vec![items.len()],
+                            elements: items.into_iter().map(|(_, item)| item).collect(),
+                            rows: false,
+                        })),
+                    })
+                    .collect();
+                (shape, elements, true)
+            }
+            _ => {
+                let items: Vec<Expr> = rows.into_iter().flat_map(|row| row.items).map(|(_, item)| item).collect();
+                (vec![items.len()], items, false)
+            }
+        };
+        Ok(Expr::TensorLiteral(Box::new(TensorLiteral { bracket, shape, elements, rows })))
+    }
+
+    // `[i * j for i in 0..3, j in 0..4]`, after the `for`. The result has
+    // one axis per variable, outermost first, so this one is 3×4.
+    fn parse_comprehension(&mut self, bracket: Token, body: Expr) -> Result<Expr, ParseError> {
+        let mut generators = Vec::new();
+        loop {
+            let name = self.consume(TokenType::Identifier, "Expect variable name after 'for'.")?.clone();
+            if !self.match_word("in") {
+                return Err(self.error("Expect 'in' after comprehension variable."));
+            }
+            generators.push((name, self.parse_expression()?));
+            if !self.match_any(&[TokenType::Comma]) {
+                break;
             }
-            Some(TokenType::LeftBracket) => self.parse_tensor_or_matrix_or_vector_literal(),
-            Some(TokenType::Sin) | Some(TokenType::Cos) | Some(TokenType::Tan) |
-            Some(TokenType::Asin) | Some(TokenType::Acos) | Some(TokenType::Atan) |
-            Some(TokenType::Sinh) | Some(TokenType::Cosh) | Some(TokenType::Tanh) |
-            Some(TokenType::Log) | Some(TokenType::Exp) | Some(TokenType::Sqrt) => self.parse_trig_function(),
-            _ => Err(ParseError::UnexpectedToken(self.peek().cloned().unwrap_or_default(), "Expected expression.".to_string())),
         }
+        self.consume(TokenType::RightBracket, "Expect ']' after comprehension.")?;
+        Ok(Expr::Comprehension(Box::new(Comprehension {
+            bracket,
+            body,
+            generators,
+        })))
     }
 
-    // ... (other methods remain largely the same)
+    // One ';'-separated row of a tensor literal. Items whose shapes are both
+    // known must match: all scalars, or all nested literals of one shape.
+    // `first_item` is an item already parsed, with the token it starts at.
+    fn parse_tensor_row(&mut self, mut first_item: Option<(Token, Expr)>) -> Result<TensorRow, ParseError> {
+        let mut items = Vec::new();
+        let mut first_known: Option<(usize, Vec<usize>)> = None;
+        loop {
+            let (item_start, item) = match first_item.take() {
+                Some(item) => item,
+                None => {
+                    let start = self.peek().cloned().unwrap_or_else(|| self.previous().clone());
+                    (start, self.parse_expression()?)
+                }
+            };
+            if let Some(shape) = known_shape(&item) {
+                match &first_known {
+                    None => first_known = Some((items.len(), shape)),
+                    Some((first, expected)) if *expected != shape => {
+                        // Nested literals side by side are rows: `[[1, 2], [3]]`.
+                        let message = if !expected.is_empty() && !shape.is_empty() {
+                            format!(
+                                "Ragged rows in tensor literal: row {} has {} but row {} has {}.",
+                                first + 1,
+                                describe_shape(expected),
+                                items.len() + 1,
+                                describe_shape(&shape)
+                            )
+                        } else {
+                            format!(
+                                "Mixed element shapes in tensor literal: element {} is {} but element {} is {}.",
+                                first + 1,
+                                describe_item(expected),
+                                items.len() + 1,
+                                describe_item(&shape)
+                            )
+                        };
+                        self.errors.push(ParseError::UnexpectedToken(Box::new(item_start.clone()), message));
+                    }
+                    Some(_) => {}
+                }
+            }
+            items.push((item_start, item));
+            if !self.match_any(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        Ok(TensorRow { items })
+    }
+
+    // "a ${x} b ${y} c" arrives as Interpolation("a ") x Interpolation(" b ")
+    // y String(" c"), so segments and expressions alternate.
+    fn parse_interpolation(&mut self) -> Result<Expr, ParseError> {
+        let mut strings = Vec::new();
+        let mut expressions = Vec::new();
+        loop {
+            let segment = self.advance().clone();
+            if let Some(token::LiteralValue::StringValue(text)) = segment.literal {
+                strings.push(text);
+            }
+            if segment.token_type == TokenType::String {
+                break;
+            }
+            expressions.push(self.parse_expression()?);
+            if !self.peek().is_some_and(continues_interpolation) {
+                return Err(self.error("Expect '}' after interpolated expression."));
+            }
+        }
+        Ok(Expr::Interpolation(Box::new(Interpolation { strings, expressions })))
+    }
 
     fn consume(&mut self, expected: TokenType, message: &str) -> Result<&Token, ParseError> {
         if self.check(&expected) {
             Ok(self.advance())
         } else {
-            Err(ParseError::UnexpectedToken(self.peek().cloned().unwrap_or_default(), message.to_string()))
+            Err(self.error(message))
+        }
+    }
+
+    // An error at the current token.
+    fn error(&self, message: &str) -> ParseError {
+        let token = self.peek().unwrap_or_else(|| self.previous()).clone();
+        ParseError::UnexpectedToken(Box::new(token), message.to_string())
+    }
+
+    fn match_any(&mut self, token_types: &[TokenType]) -> bool {
+        if token_types.iter().any(|token_type| self.check(token_type)) {
+            self.advance();
+            true
+        } else {
+            false
         }
     }
 
     fn check(&self, token_type: &TokenType) -> bool {
-        self.peek().map_or(false, |t| &t.token_type == token_type)
+        self.peek().is_some_and(|t| &t.token_type == token_type)
     }
 
     fn advance(&mut self) -> &Token {
@@ -201,8 +777,24 @@ impl Parser {
         self.previous()
     }
 
+    // Matches a contextual keyword such as `step` or `in`, which is an
+    // ordinary identifier everywhere else.
+    fn match_word(&mut self, word: &str) -> bool {
+        let found = self
+            .peek()
+            .is_some_and(|t| t.token_type == TokenType::Identifier && t.name() == word);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn check_next(&self, token_type: &TokenType) -> bool {
+        self.tokens.get(self.current + 1).is_some_and(|t| &t.token_type == token_type)
+    }
+
     fn is_at_end(&self) -> bool {
-        self.peek().map_or(true, |t| t.token_type == TokenType::EOF)
+        self.peek().is_none_or(|t| t.token_type == TokenType::Eof)
     }
 
     fn peek(&self) -> Option<&Token> {
@@ -210,6 +802,210 @@ impl Parser {
     }
 
     fn previous(&self) -> &Token {
-        &self.tokens[self.current - 1]
+        &self.tokens[self.current.saturating_sub(1)]
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Copy)]
+enum Associativity {
+    Left,
+    Right,
+}
+
+// Every infix operator with its binding power; higher binds tighter. Calls
+// and indexing bind tighter than all of these, and prefix operators sit at
+// PREFIX_POWER.
+const INFIX_OPERATORS: &[(TokenType, u8, Associativity)] = &[
+    (TokenType::Pipe, 0, Associativity::Left),
+    (TokenType::Or, 1, Associativity::Left),
+    (TokenType::OrOr, 1, Associativity::Left),
+    (TokenType::And, 2, Associativity::Left),
+    (TokenType::AndAnd, 2, Associativity::Left),
+    (TokenType::EqualEqual, 3, Associativity::Left),
+    (TokenType::BangEqual, 3, Associativity::Left),
+    (TokenType::Less, 4, Associativity::Left),
+    (TokenType::LessEqual, 4, Associativity::Left),
+    (TokenType::Greater, 4, Associativity::Left),
+    (TokenType::GreaterEqual, 4, Associativity::Left),
+    (TokenType::DotDot, 5, Associativity::Left),
+    (TokenType::DotDotEqual, 5, Associativity::Left),
+    (TokenType::Plus, 6, Associativity::Left),
+    (TokenType::Minus, 6, Associativity::Left),
+    (TokenType::Star, 7, Associativity::Left),
+    (TokenType::Slash, 7, Associativity::Left),
+    (TokenType::Percent, 7, Associativity::Left),
+    (TokenType::TensorMul, 8, Associativity::Left),
+    (TokenType::TensorProduct, 8, Associativity::Left),
+    (TokenType::TensorDot, 8, Associativity::Left),
+    (TokenType::TensorCross, 8, Associativity::Left),
+    (TokenType::Caret, 10, Associativity::Right),
+];
+
+const PREFIX_POWER: u8 = 9;
+
+// The most arguments a call may pass, and the most parameters a function
+// may declare.
+const MAX_ARGUMENTS: usize = 255;
+
+fn infix_binding(token_type: TokenType) -> Option<(u8, Associativity)> {
+    INFIX_OPERATORS
+        .iter()
+        .find(|(operator, _, _)| *operator == token_type)
+        .map(|&(_, power, associativity)| (power, associativity))
+}
+
+// The items of one row of a tensor literal, each with the token it starts at.
+struct TensorRow {
+    items: Vec<(Token, Expr)>,
+}
+
+impl TensorRow {
+    // The row's shape, if every item's shape is known while parsing. A row
+    // holding just a nested literal is that literal, so `[[1, 2]; [3, 4]]`
+    // is a 2×2 matrix rather than 2×1×2.
+    fn shape(&self, separated: bool) -> Option<Vec<usize>> {
+        let shapes = self
+            .items
+            .iter()
+            .map(|(_, item)| known_shape(item))
+            .collect::<Option<Vec<_>>>()?;
+        let item_shape = shapes.into_iter().next().unwrap_or_default();
+        if separated && self.items.len() == 1 && !item_shape.is_empty() {
+            Some(item_shape)
+        } else {
+            Some([&[self.items.len()][..], &item_shape].concat())
+        }
+    }
+}
+
+// The shape of a tensor literal item, if it is known while parsing: literals
+// are scalars, and a literal made only of them has the shape it was parsed
+// with. Anything else, such as a variable, is shaped when the program runs.
+fn known_shape(item: &Expr) -> Option<Vec<usize>> {
+    let is_scalar = |expr: &Expr| known_shape(expr).is_some_and(|shape| shape.is_empty());
+    match item {
+        Expr::LiteralValue(_) | Expr::ComplexLiteral(_) => Some(Vec::new()),
+        Expr::UnaryExpr(unary) if is_scalar(&unary.right) => Some(Vec::new()),
+        Expr::Grouping(grouping) if is_scalar(&grouping.expression) => Some(Vec::new()),
+        Expr::TensorLiteral(literal) if !literal.rows && literal.elements.iter().all(is_scalar) => {
+            Some(literal.shape.clone())
+        }
+        _ => None,
+    }
+}
+
+// Whether `token` is a string segment starting at the '}' after an
+// interpolated expression, rather than a string literal of its own.
+fn continues_interpolation(token: &Token) -> bool {
+    matches!(token.token_type, TokenType::String | TokenType::Interpolation) && token.lexeme.starts_with('}')
+}
+
+// "3 elements" for a row of scalars, "shape 2×3" for anything larger.
+fn describe_shape(shape: &[usize]) -> String {
+    match shape {
+        [1] => "1 element".to_string(),
+        [count] => format!("{} elements", count),
+        _ => format!("shape {}", format_shape(shape)),
+    }
+}
+
+fn binary(left: Expr, operator: Token, right: Expr) -> Expr {
+    Expr::BinaryExpr(Box::new(BinaryExpr { left, operator, right }))
+}
+
+// `x |> f(y)` is the call `f(x, y)` and `x |> f` is `f(x)`. The call's
+// paren is the `|>`, so an error in the call points at its stage of the
+// pipeline.
+fn pipeline(input: Expr, operator: Token, stage: Expr) -> Expr {
+    let call = match stage {
+        Expr::Call(mut call) => {
+            call.arguments.insert(0, input);
+            call.paren = operator;
+            call
+        }
+        callee => Box::new(Call {
+            callee,
+            paren: operator,
+            arguments: vec![input],
+            keywords: Vec::new(),
+        }),
+    };
+    Expr::Call(call)
+}
+
+// Numbers, strings, `true`, `false` and `nil`. Quantities such as `9.81 m/s^2`
+// are already in SI units and keep their unit for dimensional analysis.
+fn literal(token: Token) -> Expr {
+    let (value, unit) = match token.literal {
+        Some(token::LiteralValue::ImaginaryValue(imaginary)) => {
+            return Expr::ComplexLiteral(Box::new(ComplexLiteral { real: 0.0, imaginary }));
+        }
+        Some(token::LiteralValue::IntValue(n)) => (Value::Number(n as f64), None),
+        Some(token::LiteralValue::FValue(n)) => (Value::Number(n), None),
+        Some(token::LiteralValue::QuantityValue(n, unit)) => (Value::Number(n), Some(unit)),
+        Some(token::LiteralValue::StringValue(s)) => (Value::String(Rc::from(s)), None),
+        _ => match token.token_type {
+            TokenType::True => (Value::Bool(true), None),
+            TokenType::False => (Value::Bool(false), None),
+            _ => (Value::Nil, None),
+        },
+    };
+    Expr::LiteralValue(Box::new(LiteralValue { value, unit }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    fn parse_errors(source: &str) -> Vec<String> {
+        let (tokens, errors) = Scanner::new(source).scan_tokens();
+        assert!(errors.is_empty(), "scan errors: {:?}", errors);
+        let (_, errors) = Parser::new(tokens).parse();
+        errors.iter().map(|error| error.to_string()).collect()
+    }
+
+    #[test]
+    fn recovery_skips_the_blocks_of_a_failed_statement() {
+        let errors = parse_errors("if (1 +) { print 1; } else { print 2; }");
+        assert_eq!(errors, ["[line 1, column 8] Error at ')': Expected expression."]);
+    }
+
+    #[test]
+    fn recovery_reports_one_error_per_statement() {
+        let errors = parse_errors("{ print 1 +; print 2 +; } print 3 +;");
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn a_separate_string_cannot_close_an_interpolation() {
+        assert_eq!(
+            parse_errors("print \"${}\" \"b\";"),
+            ["[line 1, column 10] Error at '}\"': Expected expression."]
+        );
+        assert_eq!(
+            parse_errors("print \"a${1}\" \"b\";"),
+            ["[line 1, column 15] Error at '\"b\"': Expect ';' after value."]
+        );
+        assert!(parse_errors("print \"a ${1} b ${\"${2}\"} c\";").is_empty());
+    }
+
+    #[test]
+    fn ragged_literal_rows_are_syntax_errors() {
+        assert_eq!(
+            parse_errors("print [1, 2; 3];"),
+            ["[line 1, column 14] Error at '3': Ragged rows in tensor literal: row 1 has 2 elements but row 2 has 1 element."]
+        );
+        assert_eq!(
+            parse_errors("print [[1, 2], [3]];"),
+            ["[line 1, column 16] Error at '[': Ragged rows in tensor literal: row 1 has 2 elements but row 2 has 1 element."]
+        );
+    }
+
+    #[test]
+    fn items_that_are_not_literals_are_shaped_at_run_time() {
+        assert!(parse_errors("var v = [1, 2]; print [[3, 4], v];").is_empty());
+        assert!(parse_errors("print [[1, 2], [i for i in 0..2]];").is_empty());
+        assert!(parse_errors("var v = [1, 2]; print [v; v];").is_empty());
+    }
+}
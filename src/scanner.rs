@@ -1,108 +1,102 @@
 
-use crate::MyError;
+use std::collections::{HashMap, VecDeque};
 
+use unicode_normalization::UnicodeNormalization;
+use unicode_xid::UnicodeXID;
 
+use crate::scanner_config::ScannerConfig;
+use crate::token::{LiteralValue, Span, Token, TokenType, Trivia, TriviaKind};
+use crate::units;
+use crate::errors::MyError;
+
+
+/// Scans tokens on demand. Iterating yields every token, ending with a single
+/// EOF token, and yields each lexical error just before the token that
+/// follows it, so even very large inputs never need a materialised token list.
 pub struct Scanner<'a> {
     input: &'a str,
     position: usize,
     current_char: Option<char>,
     line_number: u64,
-}
-#[derive(Debug)]
-pub enum TokenType {
-    LEFT_PAREN,
-    RIGHT_PAREN,
-    LEFT_BRACE,
-    RIGHT_BRACE,
-    COMMA,
-    DOT,
-    MINUS,
-    PLUS,
-    SEMICOLON,
-    SLASH,
-    STAR,
-
-    BANG,
-    BANG_EQUAL,
-    EQUAL,
-    EQUAL_EQUAL,
-    GREATER,
-    GREATER_EQUAL,
-    LESS,
-    LESS_EQUAL,
-
-    IDENTIFIER,
-    STRING,
-    NUMBER,
-
-    AND,
-    CLASS,
-    ELSE,
-    FALSE,
-    FUN,
-    FOR,
-    IF,
-    NIL,
-    OR,
-    PRINT,
-    RETURN,
-    SUPER,
-    THIS,
-    TRUE,
-    VAR,
-    WHILE,
-
-    EOF,
-}
-
-
-impl std::fmt::Display for TokenType {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-#[derive(Debug)]
-pub enum LiteralValue{
-    IntValue(i64),
-    FValue(f64),
-    StringValue(String),
-    IdentifierValue(String),
-    
-}
-#[derive(Debug)]
-pub struct Token {
-    token_type:TokenType,
-    lexeme:String,
-    literal: Option<LiteralValue>,
-    line_number:u64
-}
-impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: Option<LiteralValue>, line_number: u64) -> Self {
-        Self {
-            token_type,
-            lexeme,
-            literal,
-            line_number,
-        }
-    }
+    column: u64,
+    errors: VecDeque<MyError>,
+    warnings: Vec<MyError>,
+    // Confusable skeleton of each distinct identifier seen so far, mapped to
+    // the first identifier that produced it.
+    skeletons: HashMap<String, String>,
+    lossless: bool,
+    emitted_eof: bool,
+    // A token held back by the iterator until the errors before it are out.
+    pending: Option<Token>,
+    // One entry per "${" still open: the number of unmatched '{' inside its
+    // expression and the span of the string's opening quote.
+    interpolations: Vec<(usize, Span)>,
+    config: ScannerConfig,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(input: &'a str) -> Self {
+        Self::with_config(input, ScannerConfig::default())
+    }
+
+    /// A scanner for a dialect with its own keyword and operator tables.
+    pub fn with_config(input: &'a str, config: ScannerConfig) -> Self {
         let mut scanner = Scanner {
             input,
             position: 0,
             current_char: None,
-            line_number:0,
-            
-
+            line_number: 1,
+            column: 1,
+            errors: VecDeque::new(),
+            warnings: Vec::new(),
+            skeletons: HashMap::new(),
+            lossless: false,
+            emitted_eof: false,
+            pending: None,
+            interpolations: Vec::new(),
+            config,
         };
         scanner.advance(); // Initialize the current character
         scanner
     }
 
+    /// A scanner that keeps whitespace and comments as trivia on the tokens,
+    /// so the token stream round-trips the input.
+    pub fn lossless(input: &'a str) -> Self {
+        Self::new(input).keep_trivia()
+    }
+
+    /// Switches this scanner to lossless mode, e.g. for a dialect scanner
+    /// built with `with_config`.
+    pub fn keep_trivia(mut self) -> Self {
+        self.lossless = true;
+        self
+    }
+
+    // Moves a fresh scanner to `start`, which must be a token boundary outside
+    // any string interpolation, for re-lexing part of an edited input.
+    pub(crate) fn resume_at(mut self, start: Span) -> Self {
+        self.position = start.start;
+        self.current_char = None;
+        self.advance();
+        self.line_number = start.line;
+        self.column = start.column;
+        self
+    }
+
+    pub(crate) fn in_interpolation(&self) -> bool {
+        !self.interpolations.is_empty()
+    }
+
     fn advance(&mut self) {
+        match self.current_char {
+            Some('\n') => {
+                self.line_number += 1;
+                self.column = 1;
+            }
+            Some(_) => self.column += 1,
+            None => {}
+        }
         if self.position < self.input.len() {
             self.current_char = self.input[self.position..].chars().next();
             self.position += self.current_char.unwrap_or('\0').len_utf8();
@@ -111,219 +105,608 @@ impl<'a> Scanner<'a> {
         }
     }
 
-    pub fn next_token(&mut self) -> Result<Option<Token>, MyError> {
-        self.skip_whitespace();
-
-        let token_type = match self.current_char {
-            Some('(') => TokenType::LEFT_PAREN,
-            Some(')') => TokenType::RIGHT_PAREN,
-            Some('{') => TokenType::LEFT_BRACE,
-            Some('}') => TokenType::RIGHT_BRACE,
-            Some(',') => TokenType::COMMA,
-            Some('.') => TokenType::DOT,
-            Some('-') => TokenType::MINUS,
-            Some('+') => TokenType::PLUS,
-            Some(';') => TokenType::SEMICOLON,
-            Some('*') => TokenType::STAR,
-            Some('!') => {
-                if let Some(nextchar) = self.forward_look() {
-                    if nextchar == '=' {
-                        self.advance(); // Consume the '=' in "!="
-                        TokenType::BANG_EQUAL
-                    } else {
-                        TokenType::BANG
-                    }
-                } else {
-                    TokenType::BANG
+    // Byte offset of the current character.
+    fn offset(&self) -> usize {
+        self.position - self.current_char.map_or(0, |c| c.len_utf8())
+    }
+
+    // An empty span at the current character, to be closed by `span_from`.
+    fn mark(&self) -> Span {
+        let offset = self.offset();
+        Span {
+            start: offset,
+            end: offset,
+            line: self.line_number,
+            column: self.column,
+        }
+    }
+
+    fn span_from(&self, start: Span) -> Span {
+        Span {
+            end: self.offset(),
+            ..start
+        }
+    }
+
+    fn make_token(&self, token_type: TokenType, literal: Option<LiteralValue>, start: Span) -> Token {
+        let span = self.span_from(start);
+        Token::new(
+            token_type,
+            self.input[span.start..span.end].to_string(),
+            literal,
+            span,
+        )
+    }
+
+    // Records a lexical error covering `start` up to the current character.
+    // Scanning carries on afterwards so that one pass reports every problem.
+    fn error(&mut self, message: String, start: Span) {
+        let error = MyError::ScanError {
+            message,
+            span: self.span_from(start),
+        };
+        self.errors.push_back(error);
+    }
+
+    pub fn next_token(&mut self) -> Option<Token> {
+        let mut leading_trivia = Vec::new();
+        loop {
+            self.scan_trivia(&mut leading_trivia, false);
+            let token_start = self.mark();
+
+            if let Some(mut token) = self.scan_token(token_start) {
+                let mut trailing_trivia = Vec::new();
+                self.scan_trivia(&mut trailing_trivia, true);
+                token.leading_trivia = leading_trivia;
+                token.trailing_trivia = trailing_trivia;
+                return Some(token);
+            }
+
+            if self.offset() > token_start.start {
+                // The scanned text was reported as an error; keep going.
+                self.push_trivia(&mut leading_trivia, TriviaKind::Skipped, token_start);
+                continue;
+            }
+
+            // The EOF token also carries the trivia at the end of the input.
+            if !self.emitted_eof {
+                self.emitted_eof = true;
+                while let Some((_, opening_quote)) = self.interpolations.pop() {
+                    self.error("Unterminated string interpolation".to_string(), opening_quote);
                 }
+                let mut eof = self.make_token(TokenType::Eof, None, token_start);
+                eof.leading_trivia = leading_trivia;
+                return Some(eof);
+            }
+            return None;
+        }
+    }
+
+    // Scans a single token starting at the current character, which is not
+    // trivia. Returns None at the end of input, or after skipping text that
+    // was reported as an error.
+    fn scan_token(&mut self, token_start: Span) -> Option<Token> {
+        match self.current_char {
+            None => return None, // End of input
+            // A '}' that closes "${" resumes the string it interrupted.
+            Some('}') if matches!(self.interpolations.last(), Some((0, _))) => {
+                let (_, opening_quote) = self.interpolations.pop().unwrap();
+                return self.scan_string(token_start, opening_quote);
             },
-            Some('=') => {
-                if let Some(nextchar) = self.forward_look() {
-                    if nextchar == '=' {
-                        self.advance(); // Consume the '=' in "=="
-                        TokenType::EQUAL_EQUAL
-                    } else {
-                        TokenType::EQUAL
-                    }
-                } else {
-                    TokenType::EQUAL
-                }
+            // A leading-dot float such as ".5".
+            Some('.') if self.forward_look().is_some_and(|c| c.is_ascii_digit()) => {
+                return Some(self.scan_number(token_start));
             },
-            Some('<') => {
-                if let Some(nextchar) = self.forward_look() {
-                    if nextchar == '=' {
-                        self.advance(); // Consume the '=' in "<="
-                        TokenType::LESS_EQUAL
-                    } else {
-                        TokenType::LESS
-                    }
-                } else {
-                    TokenType::LESS
-                }
+            // Plain comments are trivia, but doc comments are kept as tokens.
+            Some('/') if self.at_doc_comment() => {
+                self.skip_line();
+                let text = self.input[token_start.start + 3..self.offset()].trim_end();
+                let text = text.strip_prefix(' ').unwrap_or(text).to_string();
+                return Some(self.make_token(
+                    TokenType::DocComment,
+                    Some(LiteralValue::StringValue(text)),
+                    token_start,
+                ));
             },
-            Some('>') => {
-                if let Some(nextchar) = self.forward_look() {
-                    if nextchar == '=' {
-                        self.advance(); // Consume the '=' in ">="
-                        TokenType::GREATER_EQUAL
-                    } else {
-                        TokenType::GREATER
+            Some('"') => return self.scan_string(token_start, token_start),
+            Some(c) if c.is_ascii_digit() => return Some(self.scan_number(token_start)),
+            Some(c) if c == '_' || c.is_xid_start() => return Some(self.scan_identifier(token_start)),
+            Some(_) => {}
+        }
+
+        if let Some(token_type) = self.scan_operator() {
+            match token_type {
+                TokenType::LeftBrace => {
+                    if let Some((depth, _)) = self.interpolations.last_mut() {
+                        *depth += 1;
                     }
-                } else {
-                    TokenType::GREATER
                 }
-            },
-            Some('/') => {
-                if let Some(nextchar) = self.forward_look() {
-                    if nextchar == '/' {
-                        while let Some(c) = self.current_char {
-                            if c == '\n' {
-                                break;
-                            }
-                            self.advance();
-                        }
-                        return self.next_token(); 
-                    } else {
-                        TokenType::SLASH
+                TokenType::RightBrace => {
+                    if let Some((depth, _)) = self.interpolations.last_mut() {
+                        *depth -= 1;
                     }
-                } else {
-                    TokenType::SLASH
                 }
-            },
-            Some('\n') => {
-                self.line_number += 1;
+                _ => {}
+            }
+            return Some(self.make_token(token_type, None, token_start));
+        }
+
+        // Skip the whole run of characters that cannot start a token, so
+        // that e.g. "$$$" is reported once rather than three times.
+        self.advance();
+        while let Some(c) = self.current_char {
+            if self.can_start_token(c) {
+                break;
+            }
+            self.advance();
+        }
+        let run = &self.input[token_start.start..self.offset()];
+        let message = if run.chars().count() == 1 {
+            format!("Unexpected character '{}'", run)
+        } else {
+            format!("Unexpected characters '{}'", run)
+        };
+        self.error(message, token_start);
+        None
+    }
+
+    // Consumes the longest operator from the configured table that matches
+    // at the current character.
+    fn scan_operator(&mut self) -> Option<TokenType> {
+        let rest = &self.input[self.offset()..];
+        let (spelling, token_type) = self
+            .config
+            .operators()
+            .iter()
+            .find(|(spelling, _)| rest.starts_with(spelling.as_str()))?;
+        let (length, token_type) = (spelling.chars().count(), *token_type);
+        for _ in 0..length {
+            self.advance();
+        }
+        Some(token_type)
+    }
+
+    fn can_start_token(&self, c: char) -> bool {
+        c.is_whitespace()
+            || c.is_ascii_digit()
+            || c == '_'
+            || c == '"'
+            || c.is_xid_start()
+            || self.config.operators().iter().any(|(spelling, _)| spelling.starts_with(c))
+    }
+
+    // Identifiers follow Unicode XID rules and are compared in NFC, so `é`
+    // typed as one code point or as `e` plus a combining accent is the same
+    // name. The normalised name is the token's literal; the lexeme stays the
+    // source text.
+    fn scan_identifier(&mut self, token_start: Span) -> Token {
+        self.advance();
+        while let Some(c) = self.current_char {
+            if !c.is_xid_continue() {
+                break;
+            }
+            self.advance();
+        }
+        let lexeme = &self.input[token_start.start..self.offset()];
+        let name: String = lexeme.nfc().collect();
+
+        let token_type = self.config.keyword_type(&name).unwrap_or(TokenType::Identifier);
+        if token_type == TokenType::Identifier {
+            self.check_confusable(&name, token_start);
+            return self.make_token(token_type, Some(LiteralValue::IdentifierValue(name)), token_start);
+        }
+        self.make_token(token_type, None, token_start)
+    }
+
+    // Warns when two different identifiers would look alike, such as a Latin
+    // `a` and a Cyrillic `а`.
+    fn check_confusable(&mut self, name: &str, start: Span) {
+        let skeleton: String = unicode_security::skeleton(name).collect();
+        match self.skeletons.get(&skeleton) {
+            Some(first) if first != name => {
+                let message = format!("Identifier '{}' is easily confused with '{}'", name, first);
+                let warning = MyError::ScanWarning {
+                    message,
+                    span: self.span_from(start),
+                };
+                self.warnings.push(warning);
+            }
+            Some(_) => {}
+            None => {
+                self.skeletons.insert(skeleton, name.to_string());
+            }
+        }
+    }
+
+    /// Takes the warnings reported so far, such as confusable identifiers.
+    pub fn take_warnings(&mut self) -> Vec<MyError> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn scan_number(&mut self, token_start: Span) -> Token {
+        if self.current_char == Some('0') {
+            let radix = match self.forward_look() {
+                Some('x') | Some('X') => 16,
+                Some('o') | Some('O') => 8,
+                Some('b') | Some('B') => 2,
+                _ => 10,
+            };
+            if radix != 10 {
+                return self.scan_radix_integer(token_start, radix);
+            }
+        }
+
+        let mut is_float = false;
+        self.scan_decimal_digits();
+
+        if self.current_char == Some('.') && self.forward_look().is_some_and(|c| c.is_ascii_digit()) {
+            is_float = true;
+            self.advance(); // Consume the '.'
+            self.scan_decimal_digits();
+        }
+
+        if let Some('e') | Some('E') = self.current_char {
+            // Only an exponent if digits follow, optionally after a sign.
+            let mut rest = self.input[self.position..].chars();
+            let has_exponent = match rest.next() {
+                Some('+') | Some('-') => rest.next().is_some_and(|c| c.is_ascii_digit()),
+                Some(c) => c.is_ascii_digit(),
+                None => false,
+            };
+            if has_exponent {
+                is_float = true;
+                self.advance(); // Consume the 'e'
+                if let Some('+') | Some('-') = self.current_char {
+                    self.advance();
+                }
+                self.scan_decimal_digits();
+            }
+        }
+
+        let lexeme = &self.input[token_start.start..self.offset()];
+        let digits: String = lexeme.chars().filter(|&c| c != '_').collect();
+
+        // An 'i' suffix that does not run on into an identifier, e.g. `2i` but
+        // not `2if`, makes the literal imaginary.
+        let is_imaginary = self.current_char == Some('i')
+            && !self.forward_look().is_some_and(|c| c.is_xid_continue());
+        if is_imaginary {
+            self.advance(); // Consume the 'i'
+            let value = digits.parse::<f64>().unwrap_or(f64::INFINITY);
+            if value.is_infinite() {
+                self.error(format!("Imaginary literal '{}i' is out of range", lexeme), token_start);
+            }
+            return self.make_token(TokenType::Number, Some(LiteralValue::ImaginaryValue(value)), token_start);
+        }
+
+        let literal = if is_float {
+            let value = digits.parse::<f64>().unwrap_or(f64::INFINITY);
+            if value.is_infinite() {
+                self.error(format!("Float literal '{}' is out of range", lexeme), token_start);
+            }
+            LiteralValue::FValue(value)
+        } else {
+            match digits.parse::<i64>() {
+                Ok(value) => LiteralValue::IntValue(value),
+                Err(_) => {
+                    let message = format!("Integer literal '{}' does not fit in 64 bits", lexeme);
+                    self.error(message, token_start);
+                    LiteralValue::FValue(digits.parse::<f64>().unwrap_or(f64::INFINITY))
+                }
+            }
+        };
+
+        // A unit annotation on the same line, e.g. `9.81 m/s^2` or `3.0rad`.
+        // The value is converted to SI, so `2 km` holds 2000.
+        let rest = &self.input[self.offset()..];
+        let unit_start = self.offset() + rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        if let Some((unit, scale, length)) = units::parse_unit(&self.input[unit_start..]) {
+            while self.offset() < unit_start + length {
                 self.advance();
-                return self.next_token(); 
-            },
+            }
+            let Some(unit) = unit else {
+                let text = &self.input[unit_start..unit_start + length];
+                let message = format!("Unit exponent out of range in '{}'", text);
+                self.error(message, token_start);
+                return self.make_token(TokenType::Number, Some(literal), token_start);
+            };
+            let value = match literal {
+                LiteralValue::IntValue(value) => value as f64,
+                LiteralValue::FValue(value) => value,
+                _ => unreachable!("decimal literals are integers or floats"),
+            };
+            let literal = LiteralValue::QuantityValue(value * scale, unit);
+            return self.make_token(TokenType::Number, Some(literal), token_start);
+        }
 
-            Some(c) if c.is_digit(10) => {  
-                let start = self.position;
+        self.make_token(TokenType::Number, Some(literal), token_start)
+    }
 
-                while let Some(c) = self.current_char {
-                    if c.is_digit(10) {
-                        self.advance();
-                    } else {
-                        break;
-                    }
+    // Consumes a run of decimal digits and '_' separators, reporting a
+    // separator that is not followed by a digit.
+    fn scan_decimal_digits(&mut self) {
+        while let Some(c) = self.current_char {
+            if c == '_' {
+                let separator = self.mark();
+                self.advance();
+                if !self.current_char.is_some_and(|c| c.is_ascii_digit() || c == '_') {
+                    self.error("Digit separator '_' must be followed by a digit".to_string(), separator);
+                }
+            } else if c.is_ascii_digit() {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+    }
+
+    // Scans "0x", "0o" and "0b" integers. Every alphanumeric character after
+    // the prefix is consumed so that "0b102" is one bad literal, not two tokens.
+    fn scan_radix_integer(&mut self, token_start: Span, radix: u32) -> Token {
+        let name = match radix {
+            16 => "hexadecimal",
+            8 => "octal",
+            _ => "binary",
+        };
+        self.advance(); // Consume the '0'
+        self.advance(); // Consume the radix letter
+
+        let mut digits = String::new();
+        let mut valid = true;
+        while let Some(c) = self.current_char {
+            if !c.is_xid_continue() {
+                break;
+            }
+            if c != '_' {
+                if !c.is_digit(radix) {
+                    let digit_start = self.mark();
+                    self.advance();
+                    self.error(format!("Invalid digit '{}' in {} literal", c, name), digit_start);
+                    valid = false;
+                    continue;
                 }
+                digits.push(c);
+            }
+            self.advance();
+        }
 
-                if self.current_char == Some('.') {
+        let lexeme = &self.input[token_start.start..self.offset()];
+        let value = if digits.is_empty() {
+            if valid {
+                self.error(format!("Missing digits in {} literal '{}'", name, lexeme), token_start);
+            }
+            0
+        } else {
+            match i64::from_str_radix(&digits, radix) {
+                Ok(value) => value,
+                Err(_) => {
+                    let message = format!("Integer literal '{}' does not fit in 64 bits", lexeme);
+                    self.error(message, token_start);
+                    0
+                }
+            }
+        };
+
+        self.make_token(TokenType::Number, Some(LiteralValue::IntValue(value)), token_start)
+    }
+
+    // Scans a string segment, starting either at the opening '"' or at the
+    // '}' that ends an interpolated expression.
+    fn scan_string(&mut self, token_start: Span, opening_quote: Span) -> Option<Token> {
+        let mut value = String::new();
+        self.advance(); // Consume the opening '"' or '}'
+
+        loop {
+            match self.current_char {
+                Some('"') => break,
+                Some('$') if self.forward_look() == Some('{') => {
                     self.advance();
-                    while let Some(c) = self.current_char {
-                        if c.is_digit(10) {
-                            self.advance();
-                        } else {
-                            break;
-                        }
-                    }
-                    let lexeme = &self.input[start..self.position];
-                    return Ok(Some(Token::new(
-                        TokenType::NUMBER,
-                        lexeme.to_string(),
-                        Some(LiteralValue::FValue(lexeme.parse::<f64>().unwrap())),
-                        self.line_number,
-                    )));
+                    self.advance();
+                    self.interpolations.push((0, opening_quote));
+                    return Some(self.make_token(
+                        TokenType::Interpolation,
+                        Some(LiteralValue::StringValue(value)),
+                        token_start,
+                    ));
                 }
-            
-                let lexeme = &self.input[start..self.position];
-                return Ok(Some(Token::new(
-                    TokenType::NUMBER,
-                    lexeme.to_string(),
-                    Some(LiteralValue::IntValue(lexeme.parse::<i64>().unwrap())),
-                    self.line_number,
-                )));
-            },
+                Some('\\') => {
+                    let escape_start = self.mark();
+                    self.advance();
+                    value.push(self.scan_escape(escape_start));
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.advance();
+                }
+                None => {
+                    self.error("Unterminated string".to_string(), opening_quote);
+                    self.interpolations.clear();
+                    return None;
+                }
+            }
+        }
+
+        self.advance(); // Consume the closing '"'
+        Some(self.make_token(
+            TokenType::String,
+            Some(LiteralValue::StringValue(value)),
+            token_start,
+        ))
+    }
 
-            Some(c) if c.is_alphanumeric() => {
-                dbg!(self.position);
-                let start = self.position ;
+    // Called with the character after the backslash as the current character;
+    // `start` marks the backslash so errors cover the whole escape. Invalid
+    // escapes are reported and decode to U+FFFD so the string is still usable.
+    fn scan_escape(&mut self, start: Span) -> char {
+        let escaped = match self.current_char {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('$') => '$',
+            Some('u') => {
+                self.advance();
+                if self.current_char != Some('{') {
+                    self.error("Expected '{' after '\\u'".to_string(), start);
+                    return char::REPLACEMENT_CHARACTER;
+                }
+                self.advance();
+                let mut digits = String::new();
                 while let Some(c) = self.current_char {
-                    if c.is_whitespace() {
-                        break
-                    }
-                    if c.is_alphanumeric() || c == '_' {
-                        self.advance();
-                    } else {
+                    if !c.is_ascii_hexdigit() {
                         break;
                     }
+                    digits.push(c);
+                    self.advance();
+                }
+                if self.current_char != Some('}') {
+                    self.error("Unterminated unicode escape".to_string(), start);
+                    return char::REPLACEMENT_CHARACTER;
                 }
-                let lexeme = self.input[start -1..self.position].trim();
-                let token_type = match lexeme {
-                    "and" => TokenType::AND,
-                    "class" => TokenType::CLASS,
-                    "else" => TokenType::ELSE,
-                    "false" => TokenType::FALSE,
-                    "for" => TokenType::FOR,
-                    "fun" => TokenType::FUN,
-                    "if" => TokenType::IF,
-                    "nil" => TokenType::NIL,
-                    "or" => TokenType::OR,
-                    "print" => TokenType::PRINT,
-                    "return" => TokenType::RETURN,
-                    "super" => TokenType::SUPER,
-                    "this" => TokenType::THIS,
-                    "true" => TokenType::TRUE,
-                    "var" => TokenType::VAR,
-                    "while" => TokenType::WHILE,
-                    _ => TokenType::IDENTIFIER,
+                let code = if (1..=6).contains(&digits.len()) {
+                    u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32)
+                } else {
+                    None
                 };
-                return Ok(Some(Token::new(
-                    token_type,
-                    lexeme.to_string(),
-                    None,
-                    self.line_number,
-                )));
+                match code {
+                    Some(c) => c,
+                    None => {
+                        self.advance();
+                        self.error(format!("Invalid unicode escape '\\u{{{}}}'", digits), start);
+                        return char::REPLACEMENT_CHARACTER;
+                    }
+                }
             }
-            Some(_) => {
-                let error_message = format!(
-                    "Unexpected character '{}' on line {}",
-                    self.current_char.unwrap(),
-                    self.line_number
-                );
-                return Err(MyError::ParsingError(error_message));
+            Some(c) => {
+                self.advance();
+                self.error(format!("Unknown escape sequence '\\{}'", c), start);
+                return char::REPLACEMENT_CHARACTER;
             }
-            None => return Ok(None), // End of input
+            // The enclosing string reports itself as unterminated.
+            None => return char::REPLACEMENT_CHARACTER,
         };
-
-        let lexeme = self.current_char.unwrap().to_string(); // Convert character to string
         self.advance();
-        Ok(Some(Token::new(
-            token_type,
-            lexeme,
-            None,
-            self.line_number,
-        )))
+        escaped
     }
-    
-    
-    fn forward_look(&self) -> Option<char> {
-        dbg!(self.position + 1 <= self.input.len());
-        if self.position + 1 <= self.input.len() {
-
-         let x = self.input[self.position..].chars().next();
-         dbg!(x,self.position);
 
-         return x
-        } else {
-            None
+    // Block comments nest, so "/* a /* b */ c */" is a single comment.
+    fn skip_block_comment(&mut self, token_start: Span) {
+        self.advance(); // Consume the '/'
+        self.advance(); // Consume the '*'
+        let mut depth = 1;
+        while depth > 0 {
+            match self.current_char {
+                Some('/') if self.forward_look() == Some('*') => {
+                    self.advance();
+                    self.advance();
+                    depth += 1;
+                }
+                Some('*') if self.forward_look() == Some('/') => {
+                    self.advance();
+                    self.advance();
+                    depth -= 1;
+                }
+                Some(_) => self.advance(),
+                None => {
+                    self.error("Unterminated block comment".to_string(), token_start);
+                    return;
+                }
+            }
         }
     }
 
-    fn skip_whitespace(&mut self) {
+    fn forward_look(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    // "///" starts a doc comment, but "////" is a plain one.
+    fn at_doc_comment(&self) -> bool {
+        let rest = &self.input[self.offset()..];
+        rest.starts_with("///") && !rest.starts_with("////")
+    }
+
+    // Advances up to, but not including, the next newline.
+    fn skip_line(&mut self) {
         while let Some(c) = self.current_char {
-            if c.is_whitespace() {
-                self.advance();
-            } else {
+            if c == '\n' {
                 break;
             }
+            self.advance();
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, MyError> {
+    // Consumes whitespace and plain comments, recording them in `trivia` when
+    // scanning losslessly. Trailing trivia stops before a newline.
+    fn scan_trivia(&mut self, trivia: &mut Vec<Trivia>, trailing: bool) {
+        loop {
+            let start = self.mark();
+            let kind = match self.current_char {
+                Some('\n') if trailing => break,
+                Some('\n') => {
+                    self.advance();
+                    TriviaKind::Newline
+                }
+                Some(c) if c.is_whitespace() => {
+                    while let Some(c) = self.current_char {
+                        if c == '\n' || !c.is_whitespace() {
+                            break;
+                        }
+                        self.advance();
+                    }
+                    TriviaKind::Whitespace
+                }
+                Some('/') if self.forward_look() == Some('/') && !self.at_doc_comment() => {
+                    self.skip_line();
+                    TriviaKind::LineComment
+                }
+                Some('/') if self.forward_look() == Some('*') => {
+                    self.skip_block_comment(start);
+                    TriviaKind::BlockComment
+                }
+                _ => break,
+            };
+            self.push_trivia(trivia, kind, start);
+        }
+    }
+
+    fn push_trivia(&self, trivia: &mut Vec<Trivia>, kind: TriviaKind, start: Span) {
+        if self.lossless {
+            let span = self.span_from(start);
+            trivia.push(Trivia {
+                kind,
+                text: self.input[span.start..span.end].to_string(),
+                span,
+            });
+        }
+    }
+
+    /// Scans the whole input, returning every token that could be read along
+    /// with every lexical error encountered on the way.
+    pub fn scan_tokens(&mut self) -> (Vec<Token>, Vec<MyError>) {
         let mut tokens = Vec::new();
-        while let Some(token) = self.next_token()? {
-            tokens.push(token);
+        let mut errors = Vec::new();
+        for result in self.by_ref() {
+            match result {
+                Ok(token) => tokens.push(token),
+                Err(error) => errors.push(error),
+            }
+        }
+        (tokens, errors)
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = Result<Token, MyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.errors.is_empty() && self.pending.is_none() {
+            self.pending = self.next_token();
+        }
+        if let Some(error) = self.errors.pop_front() {
+            return Some(Err(error));
         }
-        Ok(tokens)
+        self.pending.take().map(Ok)
     }
 }
@@ -0,0 +1,111 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::token::TokenType;
+
+/// The keyword and operator tables a `Scanner` uses, so that language
+/// dialects can rename keywords or add operator spellings without touching
+/// the scanner. `ScannerConfig::default()` is the standard Lox-style language.
+///
+/// Keywords are matched against whole identifiers, so word-like aliases such
+/// as `kron` for `⊗` belong in the keyword table. Operators are punctuation
+/// and are matched longest first, so `==` wins over `=`.
+#[derive(Debug, Clone)]
+pub struct ScannerConfig {
+    keywords: HashMap<String, TokenType>,
+    operators: Vec<(String, TokenType)>,
+}
+
+impl ScannerConfig {
+    /// A configuration with no keywords and no operators.
+    pub fn empty() -> Self {
+        Self {
+            keywords: HashMap::new(),
+            operators: Vec::new(),
+        }
+    }
+
+    pub fn keyword(mut self, word: &str, token_type: TokenType) -> Self {
+        self.keywords.insert(word.to_string(), token_type);
+        self
+    }
+
+    pub fn without_keyword(mut self, word: &str) -> Self {
+        self.keywords.remove(word);
+        self
+    }
+
+    pub fn operator(mut self, spelling: &str, token_type: TokenType) -> Self {
+        self.operators.retain(|(existing, _)| existing != spelling);
+        self.operators.push((spelling.to_string(), token_type));
+        self.operators
+            .sort_by_key(|(spelling, _)| Reverse(spelling.chars().count()));
+        self
+    }
+
+    pub fn keyword_type(&self, word: &str) -> Option<TokenType> {
+        self.keywords.get(word).copied()
+    }
+
+    // Longest spellings first.
+    pub(crate) fn operators(&self) -> &[(String, TokenType)] {
+        &self.operators
+    }
+}
+
+impl Default for ScannerConfig {
+    fn default() -> Self {
+        Self::empty()
+            .keyword("and", TokenType::And)
+            .keyword("class", TokenType::Class)
+            .keyword("else", TokenType::Else)
+            .keyword("false", TokenType::False)
+            .keyword("for", TokenType::For)
+            .keyword("fun", TokenType::Fun)
+            .keyword("if", TokenType::If)
+            .keyword("nil", TokenType::Nil)
+            .keyword("or", TokenType::Or)
+            .keyword("print", TokenType::Print)
+            .keyword("return", TokenType::Return)
+            .keyword("super", TokenType::Super)
+            .keyword("this", TokenType::This)
+            .keyword("true", TokenType::True)
+            .keyword("var", TokenType::Var)
+            .keyword("while", TokenType::While)
+            .operator("(", TokenType::LeftParen)
+            .operator(")", TokenType::RightParen)
+            .operator("{", TokenType::LeftBrace)
+            .operator("}", TokenType::RightBrace)
+            .operator("[", TokenType::LeftBracket)
+            .operator("]", TokenType::RightBracket)
+            .operator(",", TokenType::Comma)
+            .operator(".", TokenType::Dot)
+            .operator("..", TokenType::DotDot)
+            .operator("..=", TokenType::DotDotEqual)
+            .operator("...", TokenType::DotDotDot)
+            .operator("-", TokenType::Minus)
+            .operator("+", TokenType::Plus)
+            .operator(";", TokenType::Semicolon)
+            .operator(":", TokenType::Colon)
+            .operator("/", TokenType::Slash)
+            .operator("*", TokenType::Star)
+            .operator("%", TokenType::Percent)
+            .operator("^", TokenType::Caret)
+            .operator("!", TokenType::Bang)
+            .operator("!=", TokenType::BangEqual)
+            .operator("=", TokenType::Equal)
+            .operator("==", TokenType::EqualEqual)
+            .operator("=>", TokenType::FatArrow)
+            .operator(">", TokenType::Greater)
+            .operator(">=", TokenType::GreaterEqual)
+            .operator("<", TokenType::Less)
+            .operator("<=", TokenType::LessEqual)
+            .operator("&&", TokenType::AndAnd)
+            .operator("||", TokenType::OrOr)
+            .operator("|>", TokenType::Pipe)
+            .operator("@", TokenType::TensorMul)
+            .operator("⊗", TokenType::TensorProduct)
+            .operator("•", TokenType::TensorDot)
+            .operator("×", TokenType::TensorCross)
+    }
+}
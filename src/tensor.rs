@@ -0,0 +1,252 @@
+use std::fmt;
+
+/// A dense tensor of real numbers. `data` holds the elements in row-major
+/// order, so the last index varies fastest; a vector has one dimension in
+/// `shape` and a matrix two.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tensor {
+    pub shape: Vec<usize>,
+    pub data: Vec<f64>,
+}
+
+impl Tensor {
+    pub fn new(shape: Vec<usize>, data: Vec<f64>) -> Self {
+        debug_assert_eq!(shape.iter().product::<usize>(), data.len());
+        Self { shape, data }
+    }
+
+    pub fn rank(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Copies out the elements picked by one `Selection` per axis. Axes
+    /// selected with `Single` are dropped from the result's shape.
+    pub fn select(&self, selections: &[Selection]) -> Tensor {
+        let mut shape = Vec::new();
+        for selection in selections {
+            if let Selection::Many(positions) = selection {
+                shape.push(positions.len());
+            }
+        }
+        let mut data = Vec::with_capacity(shape.iter().product());
+        self.gather(selections, 0, 0, &mut data);
+        Tensor::new(shape, data)
+    }
+
+    fn gather(&self, selections: &[Selection], axis: usize, offset: usize, data: &mut Vec<f64>) {
+        if axis == self.rank() {
+            data.push(self.data[offset]);
+            return;
+        }
+        let stride: usize = self.shape[axis + 1..].iter().product();
+        match &selections[axis] {
+            Selection::Single(position) => self.gather(selections, axis + 1, offset + position * stride, data),
+            Selection::Many(positions) => {
+                for position in positions {
+                    self.gather(selections, axis + 1, offset + position * stride, data);
+                }
+            }
+        }
+    }
+
+    /// The matrix product `a @ b`. A vector on the left is a row and one on
+    /// the right a column, and that axis is dropped from the result, so two
+    /// vectors give their inner product as a scalar.
+    pub fn matmul(&self, other: &Tensor) -> Result<Tensor, String> {
+        let (m, k) = match self.shape[..] {
+            [k] => (1, k),
+            [m, k] => (m, k),
+            _ => return Err(format!("'@' expects vectors or matrices but got {}.", describe_item(&self.shape))),
+        };
+        let (inner, n) = match other.shape[..] {
+            [k] => (k, 1),
+            [k, n] => (k, n),
+            _ => return Err(format!("'@' expects vectors or matrices but got {}.", describe_item(&other.shape))),
+        };
+        if k != inner {
+            return Err(format!(
+                "Cannot multiply shapes {} and {}: inner lengths {} and {} differ.",
+                format_shape(&self.shape),
+                format_shape(&other.shape),
+                k,
+                inner
+            ));
+        }
+        let mut data = vec![0.0; m * n];
+        for i in 0..m {
+            for j in 0..n {
+                data[i * n + j] = (0..k).map(|p| self.data[i * k + p] * other.data[p * n + j]).sum();
+            }
+        }
+        let mut shape = Vec::new();
+        if self.rank() == 2 {
+            shape.push(m);
+        }
+        if other.rank() == 2 {
+            shape.push(n);
+        }
+        Ok(Tensor::new(shape, data))
+    }
+
+    /// The Kronecker product `a ⊗ b`. The lower-rank operand gets leading
+    /// axes of length 1, and each axis of the result is as long as the
+    /// product of the operands' axes.
+    pub fn kron(&self, other: &Tensor) -> Tensor {
+        let rank = self.rank().max(other.rank());
+        let pad = |shape: &[usize]| [vec![1; rank - shape.len()], shape.to_vec()].concat();
+        let (a, b) = (pad(&self.shape), pad(&other.shape));
+        let shape: Vec<usize> = a.iter().zip(&b).map(|(x, y)| x * y).collect();
+        let mut data = vec![0.0; self.data.len() * other.data.len()];
+        for (i, x) in self.data.iter().enumerate() {
+            let outer = unflatten(i, &a);
+            for (j, y) in other.data.iter().enumerate() {
+                let index = unflatten(j, &b);
+                let position = (0..rank).fold(0, |position, axis| {
+                    position * shape[axis] + outer[axis] * b[axis] + index[axis]
+                });
+                data[position] = x * y;
+            }
+        }
+        Tensor::new(shape, data)
+    }
+
+    /// The dot product `a • b`: the sum of the products of corresponding
+    /// elements of two tensors of the same shape.
+    pub fn dot(&self, other: &Tensor) -> Result<Tensor, String> {
+        if self.shape != other.shape {
+            return Err(format!(
+                "'•' expects operands of the same shape but got {} and {}.",
+                describe_item(&self.shape),
+                describe_item(&other.shape)
+            ));
+        }
+        let sum = self.data.iter().zip(&other.data).map(|(x, y)| x * y).sum();
+        Ok(Tensor::new(Vec::new(), vec![sum]))
+    }
+
+    /// The cross product `a × b` of two vectors of length 3.
+    pub fn cross(&self, other: &Tensor) -> Result<Tensor, String> {
+        match (&self.data[..], &other.data[..]) {
+            ([a1, a2, a3], [b1, b2, b3]) if self.shape == [3] && other.shape == [3] => Ok(Tensor::new(
+                vec![3],
+                vec![a2 * b3 - a3 * b2, a3 * b1 - a1 * b3, a1 * b2 - a2 * b1],
+            )),
+            _ => Err(format!(
+                "'×' expects two vectors of length 3 but got {} and {}.",
+                describe_item(&self.shape),
+                describe_item(&other.shape)
+            )),
+        }
+    }
+
+    // Writes the elements in `data` as a tensor of `shape`, using the literal
+    // syntax: `[1, 2]`, `[1, 2; 3, 4]`, and '; ' between the bracketed
+    // sub-tensors of anything larger.
+    fn fmt_part(f: &mut fmt::Formatter<'_>, shape: &[usize], data: &[f64]) -> fmt::Result {
+        write!(f, "[")?;
+        match shape {
+            [] | [_] => {
+                for (i, x) in data.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", x)?;
+                }
+            }
+            [rows, columns] => {
+                for row in 0..*rows {
+                    if row > 0 {
+                        write!(f, "; ")?;
+                    }
+                    for (i, x) in data[row * columns..(row + 1) * columns].iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", x)?;
+                    }
+                }
+            }
+            [count, rest @ ..] => {
+                let size: usize = rest.iter().product();
+                for i in 0..*count {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    Self::fmt_part(f, rest, &data[i * size..(i + 1) * size])?;
+                }
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+// The index along each axis of the element at `position` in row-major order.
+fn unflatten(mut position: usize, shape: &[usize]) -> Vec<usize> {
+    let mut index = vec![0; shape.len()];
+    for axis in (0..shape.len()).rev() {
+        index[axis] = position % shape[axis];
+        position /= shape[axis];
+    }
+    index
+}
+
+/// The positions picked from one axis when indexing a tensor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection {
+    /// A single index, which removes the axis.
+    Single(usize),
+    /// A slice, which keeps the axis with these positions in order.
+    Many(Vec<usize>),
+}
+
+/// The positions of the slice `start:stop:step` along an axis of `length`,
+/// following Python: negative bounds count from the end, bounds beyond the
+/// axis are clamped, and a negative step walks backwards. `step` must not
+/// be zero.
+pub fn slice_positions(length: usize, start: Option<i64>, stop: Option<i64>, step: Option<i64>) -> Vec<usize> {
+    let length = length as i64;
+    let step = step.unwrap_or(1);
+    let resolve = |bound: i64, low: i64, high: i64| {
+        let bound = if bound < 0 { bound + length } else { bound };
+        bound.clamp(low, high)
+    };
+    let mut positions = Vec::new();
+    if step > 0 {
+        let start = start.map_or(0, |s| resolve(s, 0, length));
+        let stop = stop.map_or(length, |s| resolve(s, 0, length));
+        let mut i = start;
+        while i < stop {
+            positions.push(i as usize);
+            i += step;
+        }
+    } else {
+        let start = start.map_or(length - 1, |s| resolve(s, -1, length - 1));
+        let stop = stop.map_or(-1, |s| resolve(s, -1, length - 1));
+        let mut i = start;
+        while i > stop {
+            positions.push(i as usize);
+            i += step;
+        }
+    }
+    positions
+}
+
+impl fmt::Display for Tensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::fmt_part(f, &self.shape, &self.data)
+    }
+}
+
+/// Writes a shape as `2×3`.
+pub fn format_shape(shape: &[usize]) -> String {
+    shape.iter().map(|n| n.to_string()).collect::<Vec<_>>().join("×")
+}
+
+/// "a scalar" for an empty shape, otherwise "a tensor of shape 2×3".
+pub fn describe_item(shape: &[usize]) -> String {
+    if shape.is_empty() {
+        "a scalar".to_string()
+    } else {
+        format!("a tensor of shape {}", format_shape(shape))
+    }
+}
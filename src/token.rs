@@ -0,0 +1,231 @@
+use crate::units::Unit;
+
+/// A range of source text. `start` and `end` are byte offsets into the input;
+/// `line` and `column` are 1-based and locate `start`, with the column counted
+/// in characters rather than bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: u64,
+    pub column: u64,
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenType {
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    Comma,
+    Dot,
+    DotDot,
+    DotDotEqual,
+    DotDotDot,
+    Minus,
+    Plus,
+    Semicolon,
+    Colon,
+    Slash,
+    Star,
+    Percent,
+    Caret,
+    LeftBracket,
+    RightBracket,
+
+    Bang,
+    BangEqual,
+    Equal,
+    EqualEqual,
+    FatArrow,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    AndAnd,
+    OrOr,
+    Pipe,
+
+    // Tensor operators: '@', '⊗', '•' and '×'.
+    TensorMul,
+    TensorProduct,
+    TensorDot,
+    TensorCross,
+
+    Identifier,
+    String,
+    // A string segment ending in "${". The tokens of the embedded expression
+    // follow, then another Interpolation or the closing String segment.
+    Interpolation,
+    Number,
+
+    // A "///" comment, kept as trivia for documentation tools.
+    DocComment,
+
+    And,
+    Class,
+    Else,
+    False,
+    Fun,
+    For,
+    If,
+    Nil,
+    Or,
+    Print,
+    Return,
+    Super,
+    This,
+    True,
+    Var,
+    While,
+
+    Eof,
+}
+
+
+impl std::fmt::Display for TokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LiteralValue{
+    IntValue(i64),
+    FValue(f64),
+    StringValue(String),
+    IdentifierValue(String),
+    // The imaginary part of a literal such as `3.5i`.
+    ImaginaryValue(f64),
+    // A number with a unit annotation, such as `9.81 m/s^2`, in SI units.
+    QuantityValue(f64, Unit),
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Newline,
+    LineComment,
+    BlockComment,
+    // Text that could not be scanned and was reported as an error.
+    Skipped,
+}
+
+/// Source text between tokens, kept only when scanning losslessly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub token_type: TokenType,
+    pub lexeme: String,
+    pub literal: Option<LiteralValue>,
+    pub(crate) span: Span,
+    // Trailing trivia runs up to, but not including, the next newline;
+    // everything else before a token is its leading trivia.
+    pub(crate) leading_trivia: Vec<Trivia>,
+    pub(crate) trailing_trivia: Vec<Trivia>,
+}
+impl Token {
+    pub fn new(token_type: TokenType, lexeme: String, literal: Option<LiteralValue>, span: Span) -> Self {
+        Self {
+            token_type,
+            lexeme,
+            literal,
+            span,
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The name an identifier refers to: its NFC-normalised text, so that
+    /// differently encoded spellings of the same name are one variable.
+    pub fn name(&self) -> &str {
+        match &self.literal {
+            Some(LiteralValue::IdentifierValue(name)) => name,
+            _ => &self.lexeme,
+        }
+    }
+
+    pub fn leading_trivia(&self) -> &[Trivia] {
+        &self.leading_trivia
+    }
+
+    pub fn trailing_trivia(&self) -> &[Trivia] {
+        &self.trailing_trivia
+    }
+
+    // Applies `f` to the span of the token and of each piece of its trivia.
+    pub(crate) fn map_spans(&mut self, f: impl Fn(Span) -> Span) {
+        self.span = f(self.span);
+        for trivia in self.leading_trivia.iter_mut().chain(self.trailing_trivia.iter_mut()) {
+            trivia.span = f(trivia.span);
+        }
+    }
+
+    /// The token's source text including its trivia. Concatenating this for
+    /// every token of a lossless scan reproduces the input exactly.
+    pub fn full_text(&self) -> String {
+        let mut text = String::new();
+        for trivia in &self.leading_trivia {
+            text.push_str(&trivia.text);
+        }
+        text.push_str(&self.lexeme);
+        for trivia in &self.trailing_trivia {
+            text.push_str(&trivia.text);
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    fn round_trip(input: &str) -> String {
+        let (tokens, _) = Scanner::lossless(input).scan_tokens();
+        tokens.iter().map(Token::full_text).collect()
+    }
+
+    #[test]
+    fn lossless_tokens_reproduce_the_input() {
+        let inputs = [
+            "",
+            "  \n\t",
+            "var x = 1; // note\n/* block /* nested */ */ print x;\n",
+            "/// doc\nfun f() { return \"a ${b + 1} c\"; }   \n",
+            "print \"unterminated\nvar y = 2;",
+            "var z = 3 $$$ + 4;\n$ print z; $",
+            "print 9.81 m/s^2; /* never closed",
+        ];
+        for input in inputs {
+            assert_eq!(round_trip(input), input);
+        }
+    }
+
+    #[test]
+    fn skipped_text_is_kept_as_one_piece_of_trivia() {
+        let (tokens, errors) = Scanner::lossless("a $$$ b").scan_tokens();
+        assert_eq!(errors.len(), 1);
+        let skipped: Vec<&Trivia> = tokens
+            .iter()
+            .flat_map(|token| token.leading_trivia().iter().chain(token.trailing_trivia()))
+            .filter(|trivia| trivia.kind == TriviaKind::Skipped)
+            .collect();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].text, "$$$");
+    }
+}
@@ -0,0 +1,206 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+use unicode_xid::UnicodeXID;
+
+// Base dimensions, in the order of `Unit::dimensions`. Angle is not an SI
+// base dimension, but tracking it catches radians being added to metres.
+const BASE_SYMBOLS: [&str; 8] = ["m", "kg", "s", "A", "K", "mol", "cd", "rad"];
+
+const LENGTH: [i8; 8] = [1, 0, 0, 0, 0, 0, 0, 0];
+const MASS: [i8; 8] = [0, 1, 0, 0, 0, 0, 0, 0];
+const TIME: [i8; 8] = [0, 0, 1, 0, 0, 0, 0, 0];
+const CURRENT: [i8; 8] = [0, 0, 0, 1, 0, 0, 0, 0];
+const TEMPERATURE: [i8; 8] = [0, 0, 0, 0, 1, 0, 0, 0];
+const AMOUNT: [i8; 8] = [0, 0, 0, 0, 0, 1, 0, 0];
+const LUMINOSITY: [i8; 8] = [0, 0, 0, 0, 0, 0, 1, 0];
+const ANGLE: [i8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+const FREQUENCY: [i8; 8] = [0, 0, -1, 0, 0, 0, 0, 0];
+const FORCE: [i8; 8] = [1, 1, -2, 0, 0, 0, 0, 0];
+const ENERGY: [i8; 8] = [2, 1, -2, 0, 0, 0, 0, 0];
+const POWER: [i8; 8] = [2, 1, -3, 0, 0, 0, 0, 0];
+const PRESSURE: [i8; 8] = [-1, 1, -2, 0, 0, 0, 0, 0];
+const CHARGE: [i8; 8] = [0, 0, 1, 1, 0, 0, 0, 0];
+const VOLTAGE: [i8; 8] = [2, 1, -3, -1, 0, 0, 0, 0];
+
+// Unit symbols accepted after a numeric literal, with their dimensions and
+// the factor that converts a value in that unit to SI.
+const UNITS: &[(&str, [i8; 8], f64)] = &[
+    ("m", LENGTH, 1.0),
+    ("km", LENGTH, 1e3),
+    ("cm", LENGTH, 1e-2),
+    ("mm", LENGTH, 1e-3),
+    ("um", LENGTH, 1e-6),
+    ("μm", LENGTH, 1e-6),
+    ("nm", LENGTH, 1e-9),
+    ("s", TIME, 1.0),
+    ("ms", TIME, 1e-3),
+    ("us", TIME, 1e-6),
+    ("μs", TIME, 1e-6),
+    ("ns", TIME, 1e-9),
+    ("min", TIME, 60.0),
+    ("h", TIME, 3600.0),
+    ("kg", MASS, 1.0),
+    ("g", MASS, 1e-3),
+    ("mg", MASS, 1e-6),
+    ("A", CURRENT, 1.0),
+    ("K", TEMPERATURE, 1.0),
+    ("mol", AMOUNT, 1.0),
+    ("cd", LUMINOSITY, 1.0),
+    ("rad", ANGLE, 1.0),
+    ("deg", ANGLE, PI / 180.0),
+    ("Hz", FREQUENCY, 1.0),
+    ("N", FORCE, 1.0),
+    ("J", ENERGY, 1.0),
+    ("W", POWER, 1.0),
+    ("Pa", PRESSURE, 1.0),
+    ("C", CHARGE, 1.0),
+    ("V", VOLTAGE, 1.0),
+];
+
+/// The physical dimension of a quantity, as exponents of the base dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Unit {
+    pub dimensions: [i8; 8],
+}
+
+impl Unit {
+    pub const DIMENSIONLESS: Unit = Unit { dimensions: [0; 8] };
+    pub const ANGLE: Unit = Unit { dimensions: ANGLE };
+
+    pub fn is_dimensionless(&self) -> bool {
+        *self == Self::DIMENSIONLESS
+    }
+
+    /// The unit of a product, or `None` if an exponent no longer fits.
+    pub fn checked_mul(self, other: Unit) -> Option<Unit> {
+        let mut dimensions = self.dimensions;
+        for (d, o) in dimensions.iter_mut().zip(other.dimensions) {
+            *d = d.checked_add(o)?;
+        }
+        Some(Unit { dimensions })
+    }
+
+    pub fn checked_div(self, other: Unit) -> Option<Unit> {
+        self.checked_mul(other.checked_powi(-1)?)
+    }
+
+    pub fn checked_powi(self, exponent: i8) -> Option<Unit> {
+        let mut dimensions = self.dimensions;
+        for d in &mut dimensions {
+            *d = d.checked_mul(exponent)?;
+        }
+        Some(Unit { dimensions })
+    }
+
+    /// The square root, if every exponent is even.
+    pub fn sqrt(self) -> Option<Unit> {
+        if self.dimensions.iter().all(|d| d % 2 == 0) {
+            Some(Unit {
+                dimensions: self.dimensions.map(|d| d / 2),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_dimensionless() {
+            return write!(f, "dimensionless");
+        }
+        let factor = |symbol: &str, exponent: i8| {
+            if exponent == 1 {
+                symbol.to_string()
+            } else {
+                format!("{}^{}", symbol, exponent)
+            }
+        };
+        let numerator: Vec<String> = BASE_SYMBOLS
+            .iter()
+            .zip(self.dimensions)
+            .filter(|(_, d)| *d > 0)
+            .map(|(symbol, d)| factor(symbol, d))
+            .collect();
+        let denominator: Vec<String> = BASE_SYMBOLS
+            .iter()
+            .zip(self.dimensions)
+            .filter(|(_, d)| *d < 0)
+            .map(|(symbol, d)| factor(symbol, -d))
+            .collect();
+        if numerator.is_empty() {
+            write!(f, "1")?;
+        } else {
+            write!(f, "{}", numerator.join("·"))?;
+        }
+        if !denominator.is_empty() {
+            write!(f, "/{}", denominator.join("·"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the longest unit expression at the start of `text`, such as `rad`,
+/// `m/s^2` or `kg*m^2`, and returns its unit, the factor converting values to
+/// SI, and its length in bytes. The unit is `None` if an exponent is out of
+/// range, as in `m^100*m^100`. Operators only continue the unit when a known
+/// symbol follows immediately, so in `10 m / t` the unit is just `m`.
+pub fn parse_unit(text: &str) -> Option<(Option<Unit>, f64, usize)> {
+    let (mut unit, mut scale, mut length) = parse_unit_power(text)?;
+    loop {
+        let rest = &text[length..];
+        let (divide, operator_length) = match rest.chars().next() {
+            Some('*') => (false, 1),
+            Some('·') => (false, '·'.len_utf8()),
+            Some('/') => (true, 1),
+            _ => break,
+        };
+        let Some((next, next_scale, next_length)) = parse_unit_power(&rest[operator_length..]) else {
+            break;
+        };
+        if divide {
+            unit = unit.zip(next).and_then(|(unit, next)| unit.checked_div(next));
+            scale /= next_scale;
+        } else {
+            unit = unit.zip(next).and_then(|(unit, next)| unit.checked_mul(next));
+            scale *= next_scale;
+        }
+        length += operator_length + next_length;
+    }
+    Some((unit, scale, length))
+}
+
+// A unit symbol with an optional integer exponent, e.g. `s^-2`.
+fn parse_unit_power(text: &str) -> Option<(Option<Unit>, f64, usize)> {
+    let mut chars = text.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_xid_start() => {}
+        _ => return None,
+    }
+    let symbol_length = chars
+        .find(|(_, c)| !c.is_xid_continue())
+        .map_or(text.len(), |(i, _)| i);
+    let symbol = &text[..symbol_length];
+    let &(_, dimensions, symbol_scale) = UNITS.iter().find(|(name, _, _)| *name == symbol)?;
+    let unit = Unit { dimensions };
+
+    let rest = &text[symbol_length..];
+    if let Some(exponent_text) = rest.strip_prefix('^') {
+        let sign_length = usize::from(exponent_text.starts_with('-'));
+        let digits_length = exponent_text[sign_length..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(exponent_text.len() - sign_length);
+        if digits_length > 0 {
+            let exponent_length = sign_length + digits_length;
+            if let Ok(exponent) = exponent_text[..exponent_length].parse::<i8>() {
+                return Some((
+                    unit.checked_powi(exponent),
+                    symbol_scale.powi(exponent as i32),
+                    symbol_length + 1 + exponent_length,
+                ));
+            }
+        }
+    }
+    Some((Some(unit), symbol_scale, symbol_length))
+}
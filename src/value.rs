@@ -0,0 +1,91 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::builtins::Builtin;
+use crate::complex::Complex;
+use crate::function::Function;
+use crate::tensor::Tensor;
+
+/// A runtime value produced by evaluating an expression.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    Complex(Complex),
+    String(Rc<str>),
+    Tensor(Rc<Tensor>),
+    Builtin(&'static Builtin),
+    Function(Rc<Function>),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Bool(_) => "bool",
+            Value::Number(_) => "number",
+            Value::Complex(_) => "complex",
+            Value::String(_) => "string",
+            Value::Tensor(_) => "tensor",
+            Value::Builtin(_) => "builtin function",
+            Value::Function(_) => "function",
+        }
+    }
+
+    /// `nil` and `false` are falsey; every other value is truthy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+}
+
+// Values of different types are never equal, except that a complex number
+// with no imaginary part equals the matching real number.
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Complex(a), Value::Complex(b)) => a == b,
+            (Value::Number(a), Value::Complex(b)) | (Value::Complex(b), Value::Number(a)) => {
+                *b == Complex::from_real(*a)
+            }
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Tensor(a), Value::Tensor(b)) => a == b,
+            (Value::Builtin(a), Value::Builtin(b)) => std::ptr::eq(*a, *b),
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+// The language's display rules, used by `print` and by string interpolation.
+// Whole numbers print without a fractional part, so `3.0` shows as `3`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Complex(z) => write!(f, "{}", z),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Tensor(t) => write!(f, "{}", t),
+            Value::Builtin(builtin) => write!(f, "<builtin {}>", builtin.name),
+            Value::Function(function) => write!(f, "<fn {}>", function.name),
+        }
+    }
+}
+
+/// Builds the string for an interpolation from its literal segments and the
+/// values of the expressions between them.
+pub fn interpolate(strings: &[String], values: &[Value]) -> String {
+    let mut result = String::new();
+    for (i, segment) in strings.iter().enumerate() {
+        result.push_str(segment);
+        if let Some(value) = values.get(i) {
+            result.push_str(&value.to_string());
+        }
+    }
+    result
+}
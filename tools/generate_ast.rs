@@ -5,26 +5,90 @@ fn main() {
     let output_dir = "src/ast";
     fs::create_dir_all(output_dir).unwrap();
 
-    let ast_definitions = vec![
-        ("BinaryExpr", vec!["Expr left", "Token operator", "Expr right"]),
-        ("UnaryExpr", vec!["Token operator", "Expr right"]),
-        ("LiteralValue", vec!["f64 number", "String string"]),
-    ];
+    // Calls keep keyword arguments such as `stride: 2` apart from the
+    // positional ones, in the order they were written. A lambda's body is
+    // shared like a declared function's; `fun (x) => e` has the body
+    // `return e;`. A comprehension's generators are the variable and
+    // iterable of each `for i in ...`, outermost first.
+    define_ast(
+        output_dir,
+        "Expr",
+        &[
+            "use std::rc::Rc;",
+            "",
+            "use crate::ast::stmt::Stmt;",
+            "use crate::ast::subscript::Subscript;",
+            "use crate::token::Token;",
+            "use crate::units::Unit;",
+            "use crate::value::Value;",
+        ],
+        &[
+            ("BinaryExpr", vec!["Expr left", "Token operator", "Expr right"]),
+            ("UnaryExpr", vec!["Token operator", "Expr right"]),
+            ("LiteralValue", vec!["Value value", "Option<Unit> unit"]),
+            ("ComplexLiteral", vec!["f64 real", "f64 imaginary"]),
+            ("Interpolation", vec!["Vec<String> strings", "Vec<Expr> expressions"]),
+            ("TensorLiteral", vec!["Token bracket", "Vec<usize> shape", "Vec<Expr> elements", "bool rows"]),
+            ("Grouping", vec!["Expr expression"]),
+            ("Variable", vec!["Token name"]),
+            ("Assign", vec!["Token name", "Expr value"]),
+            ("Call", vec!["Expr callee", "Token paren", "Vec<Expr> arguments", "Vec<(Token, Expr)> keywords"]),
+            ("Index", vec!["Expr object", "Token bracket", "Vec<Subscript> subscripts"]),
+            ("Lambda", vec!["Token keyword", "Vec<Token> params", "Vec<Option<Expr>> defaults", "Rc<Vec<Stmt>> body"]),
+            ("Range", vec!["Expr start", "Token operator", "Expr end", "Option<Expr> step"]),
+            ("Comprehension", vec!["Token bracket", "Expr body", "Vec<(Token, Expr)> generators"]),
+        ],
+    );
 
-    let mut file = fs::File::create(format!("{}/expr.rs", output_dir)).unwrap();
+    // One entry between the brackets of `t[i, 1:3, ...]`.
+    define_ast(
+        output_dir,
+        "Subscript",
+        &["use crate::ast::expr::Expr;", "use crate::token::Token;"],
+        &[
+            ("IndexSubscript", vec!["Expr index"]),
+            ("SliceSubscript", vec!["Option<Expr> start", "Option<Expr> stop", "Option<Expr> step"]),
+            ("EllipsisSubscript", vec!["Token token"]),
+        ],
+    );
+
+    // Function bodies are shared with the function values created from them,
+    // which can outlive the statement that declared them.
+    define_ast(
+        output_dir,
+        "Stmt",
+        &["use std::rc::Rc;", "", "use crate::ast::expr::Expr;", "use crate::token::Token;"],
+        &[
+            ("ExpressionStmt", vec!["Expr expression"]),
+            ("PrintStmt", vec!["Expr expression"]),
+            ("VarStmt", vec!["Token name", "Option<Expr> initializer"]),
+            ("BlockStmt", vec!["Vec<Stmt> statements"]),
+            ("IfStmt", vec!["Expr condition", "Stmt then_branch", "Option<Stmt> else_branch"]),
+            ("WhileStmt", vec!["Expr condition", "Stmt body"]),
+            ("FunctionStmt", vec!["Token name", "Vec<Token> params", "Vec<Option<Expr>> defaults", "Rc<Vec<Stmt>> body"]),
+            ("ReturnStmt", vec!["Token keyword", "Option<Expr> value"]),
+        ],
+    );
+}
+
+fn define_ast(output_dir: &str, base_name: &str, imports: &[&str], ast_definitions: &[(&str, Vec<&str>)]) {
+    let mut file = fs::File::create(format!("{}/{}.rs", output_dir, base_name.to_lowercase())).unwrap();
     writeln!(file, "// Automatically generated AST").unwrap();
-    writeln!(file, "pub enum Expr {{").unwrap();
-    for (name, _) in &ast_definitions {
+    for import in imports {
+        writeln!(file, "{}", import).unwrap();
+    }
+    writeln!(file).unwrap();
+    writeln!(file, "pub enum {} {{", base_name).unwrap();
+    for (name, _) in ast_definitions {
         writeln!(file, "    {}(Box<{}>),", name, name).unwrap();
     }
     writeln!(file, "}}").unwrap();
 
-    for (name, fields) in &ast_definitions {
+    for (name, fields) in ast_definitions {
         writeln!(file, "pub struct {} {{", name).unwrap();
         for field in fields {
-            let mut parts = field.splitn(2, ' ');
-            let ty = parts.next().unwrap();
-            let name = parts.next().unwrap();
+            // The name is the last word, so types may contain spaces.
+            let (ty, name) = field.rsplit_once(' ').unwrap();
             writeln!(file, "    pub {}: {},", name, ty).unwrap();
         }
         writeln!(file, "}}").unwrap();